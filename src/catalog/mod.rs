@@ -0,0 +1,283 @@
+//! Catalog of common MCP servers
+//!
+//! Each entry is a TOML file named after the server, describing its
+//! package, the environment variables it needs, the tools it is recommended
+//! for and where it is documented. Entries shipped with multi-agent-config
+//! can be extended or replaced by team-provided ones, read from:
+//!
+//! - `MULTI_AGENT_CONFIG_CATALOG` - `PATH`-style list of directories
+//! - `catalog/` next to `config.toml`
+//!
+//! Later directories win over earlier ones and over the shipped entries.
+//! Everything is read locally, so the catalog works offline.
+
+use crate::{config::parser::read_file_utf8, diagnostics::SourceFile, error::ConfigError};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    env,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+use toml_edit::{Array, InlineTable, Item, Table, Value};
+
+/// Environment variable listing extra catalog directories
+pub const CATALOG_ENV: &str = "MULTI_AGENT_CONFIG_CATALOG";
+
+/// Name of the catalog directory next to the unified config
+pub const CATALOG_DIR_NAME: &str = "catalog";
+
+/// Entries shipped with multi-agent-config, as `(name, TOML)`
+const SHIPPED: &[(&str, &str)] = &[
+    ("brave-search", include_str!("defaults/brave-search.toml")),
+    ("fetch", include_str!("defaults/fetch.toml")),
+    ("filesystem", include_str!("defaults/filesystem.toml")),
+    ("git", include_str!("defaults/git.toml")),
+    ("github", include_str!("defaults/github.toml")),
+    ("memory", include_str!("defaults/memory.toml")),
+    (
+        "sequential-thinking",
+        include_str!("defaults/sequential-thinking.toml"),
+    ),
+    ("time", include_str!("defaults/time.toml")),
+];
+
+/// A catalog entry describing one server
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CatalogEntry {
+    /// One-line description
+    #[serde(default)]
+    pub description: String,
+
+    /// Package shorthand, e.g. `npm:@modelcontextprotocol/server-github`
+    pub package: String,
+
+    /// Arguments after the package
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// Environment variables the server needs, with what each one is
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+
+    /// Tools the server is recommended for; empty for every tool
+    #[serde(default)]
+    pub targets: Vec<String>,
+
+    /// Documentation URL
+    #[serde(default)]
+    pub docs: Option<String>,
+}
+
+impl CatalogEntry {
+    /// Server table scaffolded into `[mcp.servers]`
+    ///
+    /// Required environment variables become `${NAME}` references to the
+    /// shell environment, which also work with `expand = "runtime"`. Packages
+    /// run over STDIO, which is written out rather than inferred.
+    #[must_use]
+    pub fn server_table(&self) -> Table {
+        let mut table = Table::new();
+        table.insert("transport", Item::Value("stdio".into()));
+        table.insert("package", Item::Value(self.package.as_str().into()));
+        if !self.args.is_empty() {
+            table.insert("args", Item::Value(Value::Array(strings(&self.args))));
+        }
+        if !self.targets.is_empty() {
+            table.insert("targets", Item::Value(Value::Array(strings(&self.targets))));
+        }
+        if !self.env.is_empty() {
+            let env: InlineTable = self
+                .env
+                .keys()
+                .map(|name| (name.as_str(), Value::from(format!("${{{name}}}"))))
+                .collect();
+            table.insert("env", Item::Value(Value::InlineTable(env)));
+        }
+
+        let mut comment = String::from("\n");
+        for line in [Some(&self.description), self.docs.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter(|line| !line.is_empty())
+        {
+            let _ = writeln!(comment, "# {line}");
+        }
+        table.decor_mut().set_prefix(comment);
+        table
+    }
+}
+
+/// Array of strings
+fn strings(values: &[String]) -> Array {
+    values.iter().map(String::as_str).collect()
+}
+
+/// Catalog entries by name
+#[derive(Debug, Clone, Default)]
+pub struct Catalog {
+    entries: BTreeMap<String, CatalogEntry>,
+}
+
+impl Catalog {
+    /// The entries shipped with multi-agent-config
+    ///
+    /// # Panics
+    ///
+    /// Never; the shipped entries are checked by the tests
+    #[must_use]
+    pub fn shipped() -> Self {
+        let entries = SHIPPED
+            .iter()
+            .map(|(name, text)| {
+                let entry = toml::from_str(text).expect("shipped catalog entry is valid");
+                ((*name).to_string(), entry)
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// The shipped entries and those in the catalog directories for a config
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a catalog directory or entry cannot be read, or an
+    /// entry is not valid
+    pub fn load(config_path: &Path) -> Result<Self, ConfigError> {
+        let mut catalog = Self::shipped();
+        for dir in catalog_dirs(config_path) {
+            catalog.load_dir(&dir)?;
+        }
+        Ok(catalog)
+    }
+
+    /// Add the `*.toml` entries in a directory, replacing ones of the same
+    /// name; a missing directory adds nothing
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory or an entry cannot be read, or an
+    /// entry is not valid
+    pub fn load_dir(&mut self, dir: &Path) -> Result<(), ConfigError> {
+        if !dir.is_dir() {
+            return Ok(());
+        }
+        let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+            .collect();
+        paths.sort();
+
+        for path in paths {
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+            let text = read_file_utf8(&path)?;
+            let entry = toml::from_str(&text).map_err(|e| {
+                e.span().map_or_else(
+                    || ConfigError::parse_error(e.message(), 0),
+                    |span| {
+                        let source = SourceFile::new(&path, text.as_str());
+                        ConfigError::parse_error_at(e.message(), &source, span)
+                    },
+                )
+            })?;
+            self.entries.insert(name.to_string(), entry);
+        }
+        Ok(())
+    }
+
+    /// Entry for a server
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&CatalogEntry> {
+        self.entries.get(name)
+    }
+
+    /// Entries, sorted by name
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &CatalogEntry)> {
+        self.entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+}
+
+/// Team catalog directories for a config, lowest precedence first
+#[must_use]
+pub fn catalog_dirs(config_path: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = env::var_os(CATALOG_ENV)
+        .map(|paths| env::split_paths(&paths).collect())
+        .unwrap_or_default();
+    dirs.push(config_path.with_file_name(CATALOG_DIR_NAME));
+    dirs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_shipped_entries_are_valid() {
+        let catalog = Catalog::shipped();
+        assert_eq!(catalog.entries().count(), SHIPPED.len());
+        for (name, entry) in catalog.entries() {
+            assert!(
+                crate::config::package::PackageSpec::parse(&entry.package).is_ok(),
+                "{name}: {}",
+                entry.package
+            );
+            assert!(!entry.description.is_empty(), "{name}");
+            assert_eq!(
+                entry.server_table()["transport"].as_str(),
+                Some("stdio"),
+                "{name}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_team_entries_replace_shipped() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("github.toml"),
+            r#"
+description = "GitHub Enterprise"
+package = "npm:@acme/github-mcp@2.0.0"
+targets = ["claude-code"]
+
+[env]
+GHE_TOKEN = "Token for ghe.acme.dev"
+"#,
+        )
+        .unwrap();
+        fs::write(dir.path().join("README.md"), "not an entry").unwrap();
+
+        let mut catalog = Catalog::shipped();
+        catalog.load_dir(dir.path()).unwrap();
+        let github = catalog.get("github").unwrap();
+        assert_eq!(github.package, "npm:@acme/github-mcp@2.0.0");
+
+        let table = github.server_table().to_string();
+        assert_eq!(
+            table,
+            "transport = \"stdio\"\npackage = \"npm:@acme/github-mcp@2.0.0\"\ntargets = [\"claude-code\"]\nenv = { \
+             GHE_TOKEN = \"${GHE_TOKEN}\" }\n"
+        );
+    }
+
+    #[test]
+    fn test_invalid_team_entry() {
+        let dir = TempDir::new().unwrap();
+        fs::write(
+            dir.path().join("bad.toml"),
+            "package = \"npm:x\"\nurl = \"y\"\n",
+        )
+        .unwrap();
+
+        let err = Catalog::shipped().load_dir(dir.path()).unwrap_err();
+        assert!(err.to_string().contains("unknown field `url`"));
+    }
+}
@@ -0,0 +1,290 @@
+//! Add command implementation
+
+use clap::{ArgGroup, Args};
+use multi_agent_config::{
+    catalog::{Catalog, CatalogEntry},
+    config::edit::ConfigEditor,
+    error::MultiAgentError,
+};
+use std::path::Path;
+use toml_edit::{Array, InlineTable, Item, Table, Value};
+
+/// Arguments of the add command
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("source")
+        .required(true)
+        .args(["from_catalog", "command", "package", "url"])
+))]
+pub struct AddArgs {
+    /// Server name
+    pub name: String,
+
+    /// Scaffold the server from the catalog entry of the same name
+    #[arg(long)]
+    pub from_catalog: bool,
+
+    /// Command that starts a STDIO server
+    #[arg(long)]
+    pub command: Option<String>,
+
+    /// Package that runs a STDIO server, e.g. npm:@scope/name@1.0.0
+    #[arg(long)]
+    pub package: Option<String>,
+
+    /// URL of a remote server
+    #[arg(long)]
+    pub url: Option<String>,
+
+    /// Argument for the command or package (repeatable)
+    #[arg(long = "arg", value_name = "ARG", allow_hyphen_values = true)]
+    pub args: Vec<String>,
+
+    /// Tool to compile the server for (repeatable or comma-separated)
+    #[arg(long = "target", value_name = "TOOL", value_delimiter = ',')]
+    pub targets: Vec<String>,
+
+    /// Environment variable as KEY=VALUE (repeatable)
+    #[arg(long = "env", value_name = "KEY=VALUE")]
+    pub env: Vec<String>,
+}
+
+/// Add a server to the config
+///
+/// The server is appended to `[mcp.servers]`, either from the flags or from
+/// its catalog entry. With `--from-catalog`, `--arg` and `--target` replace
+/// the entry's arguments and targets and `--env` adds to its variables;
+/// each variable the entry needs becomes a `${NAME}` reference. The config
+/// is validated before it is saved and the previous file is backed up.
+///
+/// # Arguments
+///
+/// * `config_path` - Path to unified configuration file
+/// * `add` - Server name and fields
+/// * `verbose` - Enable verbose output
+///
+/// # Returns
+///
+/// * `Ok(())` - Server added
+/// * `Err(MultiAgentError)` - Error adding the server
+///
+/// # Errors
+///
+/// Returns error if the catalog entry is unknown, an `--env` is not
+/// `KEY=VALUE`, the server already exists, or the edited config is invalid
+/// or cannot be written
+pub fn add_command(
+    config_path: &Path,
+    add: &AddArgs,
+    verbose: bool,
+) -> Result<(), MultiAgentError> {
+    let name = add.name.as_str();
+    let catalog = Catalog::load(config_path)?;
+    let entry = if add.from_catalog {
+        Some(catalog.get(name).ok_or_else(|| {
+            let names: Vec<&str> = catalog.entries().map(|(name, _)| name).collect();
+            MultiAgentError::CliError(format!(
+                "No catalog entry '{name}'; available: {}",
+                names.join(", ")
+            ))
+        })?)
+    } else {
+        None
+    };
+
+    let mut server = entry.map(CatalogEntry::server_table).unwrap_or_default();
+    apply_flags(&mut server, add)?;
+    let has_package = server.contains_key("package");
+
+    let mut editor = ConfigEditor::open(config_path)?;
+    editor
+        .add_server(name, server)
+        .map_err(MultiAgentError::CliError)?;
+    let backup = editor.save()?;
+
+    println!("Added server '{name}' to {}", config_path.display());
+    if let (true, Some(backup)) = (verbose, backup) {
+        println!("  Created backup: {}", backup.display());
+    }
+    if let Some(entry) = entry {
+        print_catalog_notes(entry);
+    }
+    if has_package {
+        println!("Run 'multi-agent-config lock' to pin its version");
+    }
+    Ok(())
+}
+
+/// Print where a catalog entry is documented and what it needs
+fn print_catalog_notes(entry: &CatalogEntry) {
+    println!("  Package: {}", entry.package);
+    if let Some(docs) = &entry.docs {
+        println!("  Docs: {docs}");
+    }
+    if !entry.env.is_empty() {
+        println!(
+            "Export these environment variables (or change the references to {{NAME}} to use \
+             [env] and the secrets file):"
+        );
+        for (var, description) in &entry.env {
+            println!("  {var}: {description}");
+        }
+    }
+}
+
+/// Set the fields given as flags on a server table
+///
+/// The transport follows from `--url` or `--command`/`--package` and is
+/// written out, since inferring it is deprecated.
+fn apply_flags(server: &mut Table, add: &AddArgs) -> Result<(), MultiAgentError> {
+    let transport = if add.url.is_some() {
+        Some("http")
+    } else if add.command.is_some() || add.package.is_some() {
+        Some("stdio")
+    } else {
+        None
+    };
+    if let Some(transport) = transport {
+        server.insert("transport", toml_edit::value(transport));
+    }
+
+    let fields = [
+        ("command", &add.command),
+        ("package", &add.package),
+        ("url", &add.url),
+    ];
+    for (key, value) in fields {
+        if let Some(value) = value {
+            server.insert(key, toml_edit::value(value.as_str()));
+        }
+    }
+
+    let list = |values: &[String]| {
+        let array: Array = values.iter().map(String::as_str).collect();
+        Item::Value(Value::Array(array))
+    };
+    if !add.args.is_empty() {
+        server.insert("args", list(&add.args));
+    }
+    if !add.targets.is_empty() {
+        server.insert("targets", list(&add.targets));
+    }
+
+    if add.env.is_empty() {
+        return Ok(());
+    }
+    let mut env = server
+        .get("env")
+        .and_then(Item::as_inline_table)
+        .cloned()
+        .unwrap_or_else(InlineTable::new);
+    for pair in &add.env {
+        let (key, value) = pair.split_once('=').ok_or_else(|| {
+            MultiAgentError::CliError(format!("--env '{pair}' must be KEY=VALUE"))
+        })?;
+        env.insert(key, Value::from(value));
+    }
+    server.insert("env", Item::Value(Value::InlineTable(env)));
+    Ok(())
+}
+
+/// List the catalog entries available to a config
+///
+/// # Errors
+///
+/// Returns error if a catalog directory or entry cannot be read
+pub fn catalog_command(config_path: &Path, verbose: bool) -> Result<(), MultiAgentError> {
+    let catalog = Catalog::load(config_path)?;
+    for (name, entry) in catalog.entries() {
+        println!("{name:<24} {}", entry.description);
+        if verbose {
+            println!("{:<24} {}", "", entry.package);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn add_args(name: &str) -> AddArgs {
+        AddArgs {
+            name: name.to_string(),
+            from_catalog: false,
+            command: None,
+            package: None,
+            url: None,
+            args: Vec::new(),
+            targets: Vec::new(),
+            env: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_add_command_from_catalog() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(&config_path, "[settings]\nversion = \"1.0\"\n").unwrap();
+
+        let github = AddArgs {
+            from_catalog: true,
+            targets: vec!["cursor".to_string()],
+            ..add_args("github")
+        };
+        add_command(&config_path, &github, false).unwrap();
+        let content = fs::read_to_string(&config_path).unwrap();
+        assert!(content.contains("[mcp.servers.github]"));
+        assert!(content.contains("package = \"npm:@modelcontextprotocol/server-github\""));
+        assert!(content.contains("targets = [\"cursor\"]"));
+        assert!(content.contains(
+            "env = { GITHUB_PERSONAL_ACCESS_TOKEN = \"${GITHUB_PERSONAL_ACCESS_TOKEN}\" }"
+        ));
+
+        assert!(add_command(&config_path, &github, false).is_err());
+        let unknown = AddArgs {
+            from_catalog: true,
+            ..add_args("no-such-server")
+        };
+        assert!(add_command(&config_path, &unknown, false).is_err());
+    }
+
+    #[test]
+    fn test_add_command_from_flags() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(&config_path, "[settings]\nversion = \"1.0\"\n").unwrap();
+
+        let add = AddArgs {
+            command: Some("npx".to_string()),
+            args: vec!["-y".to_string(), "pkg".to_string()],
+            targets: vec!["cursor".to_string(), "codex".to_string()],
+            env: vec!["KEY={TOKEN}".to_string()],
+            ..add_args("myserver")
+        };
+        add_command(&config_path, &add, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            "[settings]\nversion = \"1.0\"\n\n[mcp.servers.myserver]\ntransport = \"stdio\"\ncommand = \
+             \"npx\"\nargs = [\"-y\", \"pkg\"]\ntargets = [\"cursor\", \"codex\"]\nenv = { KEY = \"{TOKEN}\" }\n"
+        );
+
+        let bad = AddArgs {
+            command: Some("npx".to_string()),
+            env: vec!["KEY".to_string()],
+            ..add_args("other")
+        };
+        assert!(add_command(&config_path, &bad, false).is_err());
+
+        let remote = AddArgs {
+            url: Some("https://example.com/mcp".to_string()),
+            ..add_args("remote")
+        };
+        add_command(&config_path, &remote, false).unwrap();
+        assert!(fs::read_to_string(&config_path).unwrap().ends_with(
+            "[mcp.servers.remote]\ntransport = \"http\"\nurl = \"https://example.com/mcp\"\n"
+        ));
+    }
+}
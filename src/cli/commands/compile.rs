@@ -1,16 +1,31 @@
 //! Compile command implementation
 
 use multi_agent_config::{
-    config::{ToolName, parse_and_expand_config, validate_config},
+    config::{
+        MultiAgentConfig, ToolName, check_config,
+        command::{ResolvedCommand, pin_commands, search_path},
+        package::{Lockfile, lock_file_path, pin_packages},
+        parse_and_expand_config_with_secrets,
+    },
+    diagnostics::{Diagnostic, Severity, SourceFile},
     error::MultiAgentError,
-    file_ops::{StateTracker, create_backup, hash_file, write_file_atomic},
+    expand::SecretRegistry,
+    file_ops::{
+        GeneratedFile, StateTracker, create_backup, default_state_path, hash_file,
+        write_file_atomic,
+    },
     transform::{
-        transform_for_claude_code, transform_for_codex, transform_for_cursor,
-        transform_for_opencode,
+        Launcher, PermissionRules, TransformOptions, claude_code::default_settings_path,
+        merge_permissions, transform_for_tool,
     },
 };
 use std::path::{Path, PathBuf};
 
+/// Convert an I/O error into a file operation error
+const fn io_error(e: std::io::Error) -> MultiAgentError {
+    MultiAgentError::FileOpError(multi_agent_config::file_ops::writer::FileOpError::Io(e))
+}
+
 /// Get output path for a tool's configuration
 fn get_tool_config_path(tool: ToolName) -> PathBuf {
     let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
@@ -30,6 +45,125 @@ fn get_tool_config_path(tool: ToolName) -> PathBuf {
     }
 }
 
+/// Write a tool's launcher scripts and remove the ones it no longer uses
+///
+/// # Errors
+///
+/// Returns error if a launcher cannot be written or hashed
+fn write_launchers(
+    tool: ToolName,
+    launchers: &[Launcher],
+    state_tracker: &mut StateTracker,
+    verbose: bool,
+) -> Result<(), MultiAgentError> {
+    let mut written = Vec::new();
+    for launcher in launchers {
+        write_file_atomic(&launcher.path, &launcher.content, Some(0o700))?;
+        written.push(GeneratedFile {
+            tool: tool.to_string(),
+            path: launcher.path.clone(),
+            timestamp: chrono::Utc::now(),
+            hash: hash_file(&launcher.path).map_err(io_error)?,
+        });
+        if verbose {
+            println!("  Wrote launcher: {}", launcher.path.display());
+        }
+    }
+
+    for stale in state_tracker.replace_launchers(&tool.to_string(), written) {
+        match std::fs::remove_file(&stale.path) {
+            Ok(()) if verbose => println!("  Removed launcher: {}", stale.path.display()),
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => eprintln!(
+                "Warning: Cannot remove launcher {}: {e}",
+                stale.path.display()
+            ),
+        }
+    }
+    Ok(())
+}
+
+/// Write Claude Code permission rules into its settings file
+///
+/// Leaves the file alone when there are no rules to write or remove.
+///
+/// # Errors
+///
+/// Returns error if the settings file cannot be read, parsed or written
+fn write_permissions(
+    rules: &PermissionRules,
+    state_tracker: &mut StateTracker,
+    dry_run: bool,
+    verbose: bool,
+) -> Result<(), MultiAgentError> {
+    if rules.is_empty() && state_tracker.permissions().is_empty() {
+        return Ok(());
+    }
+
+    let path = default_settings_path();
+    if dry_run {
+        println!("Would update permissions in: {}", path.display());
+        return Ok(());
+    }
+
+    let existing = match std::fs::read_to_string(&path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(io_error(e)),
+    };
+    let content = merge_permissions(existing.as_deref(), rules, state_tracker.permissions())
+        .map_err(MultiAgentError::TransformError)?;
+    if existing.as_deref() != Some(content.as_str()) {
+        if let Ok(Some(backup_path)) = create_backup(&path) {
+            if verbose {
+                println!("  Created backup: {}", backup_path.display());
+            }
+        }
+        write_file_atomic(&path, &content, Some(0o600))?;
+        println!("  permissions -> {}", path.display());
+    }
+    state_tracker.set_permissions(rules.all());
+    Ok(())
+}
+
+/// Pin packages to their locked versions and commands to absolute paths
+///
+/// # Returns
+///
+/// The commands pinned for servers with `resolve_command`
+fn pin_servers(
+    config: &mut MultiAgentConfig,
+    config_path: &Path,
+    secrets: &SecretRegistry,
+    verbose: bool,
+) -> Result<Vec<ResolvedCommand>, MultiAgentError> {
+    // Run packages at the versions in the config or the lock file
+    let lock =
+        Lockfile::load(&lock_file_path(config_path)).map_err(MultiAgentError::PackageError)?;
+    for warning in pin_packages(config, &lock) {
+        eprintln!("Warning: {}", secrets.redact(&warning));
+    }
+
+    // Pin commands to absolute paths for servers with resolve_command
+    let search_path = search_path(config.settings.as_ref(), None);
+    let (resolved, warnings) = pin_commands(config, &search_path);
+    for warning in &warnings {
+        eprintln!("Warning: {}", secrets.redact(warning));
+    }
+    if verbose {
+        for pinned in &resolved {
+            println!(
+                "Resolved {}: {} -> {}",
+                pinned.server,
+                pinned.command,
+                pinned.path.display()
+            );
+        }
+    }
+    Ok(resolved)
+}
+
 /// Compile and write tool configurations
 ///
 /// # Arguments
@@ -38,6 +172,7 @@ fn get_tool_config_path(tool: ToolName) -> PathBuf {
 /// * `tools` - Specific tools to compile for (empty = all matching servers)
 /// * `dry_run` - Show what would be done without writing
 /// * `verbose` - Enable verbose output
+/// * `show_secrets` - Print secret values instead of `<redacted>`
 ///
 /// # Returns
 ///
@@ -52,24 +187,33 @@ pub fn compile_command(
     tools: &[String],
     dry_run: bool,
     verbose: bool,
+    show_secrets: bool,
 ) -> Result<(), MultiAgentError> {
     // Parse and expand configuration
-    let config = parse_and_expand_config(config_path)?;
+    let (mut config, mut secrets) = parse_and_expand_config_with_secrets(config_path)?;
+    secrets.set_reveal(show_secrets);
 
-    // Validate
-    if let Err(errors) = validate_config(&config) {
-        eprintln!("Validation failed:");
-        for error in &errors {
-            eprintln!("  - {error}");
-        }
+    // Validate; warnings are reported but only errors stop the compile
+    let source = SourceFile::load(config_path);
+    let findings = check_config(&config);
+    let error_count = findings
+        .iter()
+        .filter(|finding| finding.severity == Severity::Error)
+        .count();
+    for finding in findings.iter().filter(|f| f.severity != Severity::Info) {
+        let diagnostic = Diagnostic::from_validation_error(finding, &source);
+        eprintln!("{}", secrets.redact(&diagnostic.render(&source)));
+    }
+    if error_count > 0 {
         return Err(MultiAgentError::Config(
             multi_agent_config::error::ConfigError::ValidationError(format!(
-                "{} error(s)",
-                errors.len()
+                "{error_count} error(s)"
             )),
         ));
     }
 
+    let resolved = pin_servers(&mut config, config_path, &secrets, verbose)?;
+
     // Determine target tools
     let target_tools: Vec<ToolName> = if tools.is_empty() {
         ToolName::concrete_tools()
@@ -77,20 +221,11 @@ pub fn compile_command(
         tools.iter().filter_map(|t| ToolName::from_str(t)).collect()
     };
 
-    // Get default targets from settings
-    let default_targets = config
-        .settings
-        .as_ref()
-        .map(|s| s.default_targets.clone())
-        .unwrap_or_default();
+    // Get transformation options from settings
+    let options = TransformOptions::from_settings(config.settings.as_ref());
 
     // Load state tracker
-    let state_path = dirs::config_dir()
-        .unwrap_or_else(|| PathBuf::from("."))
-        .join("multi-agent-config")
-        .join("state")
-        .join("generated.json");
-    let mut state_tracker = StateTracker::load(&state_path).map_err(|e| {
+    let mut state_tracker = StateTracker::load(&default_state_path()).map_err(|e| {
         MultiAgentError::FileOpError(multi_agent_config::file_ops::writer::FileOpError::Io(e))
     })?;
 
@@ -103,30 +238,31 @@ pub fn compile_command(
         }
 
         // Transform configuration
-        let output_content = match tool {
-            ToolName::Cursor => transform_for_cursor(&config.mcp.servers, &default_targets)
-                .map_err(MultiAgentError::TransformError)?,
-            ToolName::Opencode => transform_for_opencode(&config.mcp.servers, &default_targets)
-                .map_err(MultiAgentError::TransformError)?,
-            ToolName::Codex => transform_for_codex(&config.mcp.servers, &default_targets)
-                .map_err(MultiAgentError::TransformError)?,
-            ToolName::ClaudeCode => {
-                transform_for_claude_code(&config.mcp.servers, &default_targets)
-                    .map_err(MultiAgentError::TransformError)?
-            }
-            ToolName::All => continue,
-        };
+        if tool == ToolName::All {
+            continue;
+        }
+        let output = transform_for_tool(tool, &config.mcp.servers, &options)
+            .map_err(|e| MultiAgentError::TransformError(secrets.redact(&e)))?;
+        for warning in &output.warnings {
+            eprintln!("Warning: {}", secrets.redact(warning));
+        }
+        let output_content = output.content;
 
         let output_path = get_tool_config_path(tool);
 
+        if tool == ToolName::ClaudeCode {
+            write_permissions(&output.permissions, &mut state_tracker, dry_run, verbose)?;
+        }
+
         if dry_run {
             println!("Would write to: {}", output_path.display());
+            for launcher in &output.launchers {
+                println!("Would write launcher: {}", launcher.path.display());
+            }
             if verbose {
                 println!("Content preview (first 200 chars):");
-                println!(
-                    "{}...",
-                    output_content.chars().take(200).collect::<String>()
-                );
+                let preview: String = output_content.chars().take(200).collect();
+                println!("{}...", secrets.redact(&preview));
             }
         } else {
             // Create backup if file exists
@@ -136,15 +272,14 @@ pub fn compile_command(
                 }
             }
 
+            // Write launchers first, the configuration refers to them
+            write_launchers(tool, &output.launchers, &mut state_tracker, verbose)?;
+
             // Write file
             write_file_atomic(&output_path, &output_content, Some(0o600))?;
 
             // Compute hash and update state
-            let hash = hash_file(&output_path).map_err(|e| {
-                MultiAgentError::FileOpError(multi_agent_config::file_ops::writer::FileOpError::Io(
-                    e,
-                ))
-            })?;
+            let hash = hash_file(&output_path).map_err(io_error)?;
             state_tracker.add_generated_file(&tool.to_string(), output_path.clone(), hash);
 
             println!("  {} -> {}", tool, output_path.display());
@@ -154,6 +289,7 @@ pub fn compile_command(
 
     // Save state
     if !dry_run {
+        state_tracker.set_resolved_commands(resolved);
         state_tracker.save().map_err(|e| {
             MultiAgentError::FileOpError(multi_agent_config::file_ops::writer::FileOpError::Io(e))
         })?;
@@ -1,16 +1,42 @@
 //! Diff command implementation
 
 use multi_agent_config::{
-    config::{ToolName, parse_and_expand_config, validate_config},
+    config::{
+        ToolName, check_config,
+        command::{pin_commands, search_path},
+        package::{Lockfile, lock_file_path, pin_packages},
+        parse_and_expand_config_with_secrets,
+    },
+    diagnostics::{Diagnostic, Severity, SourceFile},
     error::MultiAgentError,
-    file_ops::generate_file_diff,
+    expand::secrets::SecretRegistry,
+    file_ops::{StateTracker, default_state_path, generate_file_diff},
     transform::{
-        transform_for_claude_code, transform_for_codex, transform_for_cursor,
-        transform_for_opencode,
+        TransformOptions, claude_code::default_settings_path, merge_permissions, transform_for_tool,
     },
 };
 use std::path::{Path, PathBuf};
 
+/// Print the diff for one generated file and any secrets it would store
+fn print_file_diff(label: &str, path: &Path, content: &str, secrets: &SecretRegistry) {
+    let diff = generate_file_diff(path, content);
+
+    println!("=== {label} ({}) ===", path.display());
+    println!("{}", secrets.redact(&diff));
+
+    // Report secrets that would be stored in plaintext
+    let at_rest = secrets.names_found_in(content);
+    if !at_rest.is_empty() {
+        println!(
+            "Secrets at rest in {}: {}",
+            path.display(),
+            at_rest.join(", ")
+        );
+        println!("  (use expand = \"runtime\" to emit runtime references instead)");
+    }
+    println!();
+}
+
 /// Get output path for a tool's configuration
 fn get_tool_config_path(tool: ToolName) -> PathBuf {
     let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
@@ -37,6 +63,7 @@ fn get_tool_config_path(tool: ToolName) -> PathBuf {
 /// * `config_path` - Path to unified configuration file
 /// * `tools` - Specific tools to show diff for (empty = all matching servers)
 /// * `verbose` - Enable verbose output
+/// * `show_secrets` - Print secret values instead of `<redacted>`
 ///
 /// # Returns
 ///
@@ -50,24 +77,45 @@ pub fn diff_command(
     config_path: &Path,
     tools: &[String],
     verbose: bool,
+    show_secrets: bool,
 ) -> Result<(), MultiAgentError> {
     // Parse and expand configuration
-    let config = parse_and_expand_config(config_path)?;
-
-    // Validate
-    if let Err(errors) = validate_config(&config) {
-        eprintln!("Validation failed:");
-        for error in &errors {
-            eprintln!("  - {error}");
-        }
+    let (mut config, mut secrets) = parse_and_expand_config_with_secrets(config_path)?;
+    secrets.set_reveal(show_secrets);
+
+    // Validate; warnings are reported but only errors stop the diff
+    let source = SourceFile::load(config_path);
+    let findings = check_config(&config);
+    let error_count = findings
+        .iter()
+        .filter(|finding| finding.severity == Severity::Error)
+        .count();
+    for finding in findings.iter().filter(|f| f.severity != Severity::Info) {
+        let diagnostic = Diagnostic::from_validation_error(finding, &source);
+        eprintln!("{}", secrets.redact(&diagnostic.render(&source)));
+    }
+    if error_count > 0 {
         return Err(MultiAgentError::Config(
             multi_agent_config::error::ConfigError::ValidationError(format!(
-                "{} error(s)",
-                errors.len()
+                "{error_count} error(s)"
             )),
         ));
     }
 
+    // Run packages at the versions in the config or the lock file
+    let lock =
+        Lockfile::load(&lock_file_path(config_path)).map_err(MultiAgentError::PackageError)?;
+    for warning in pin_packages(&mut config, &lock) {
+        eprintln!("Warning: {}", secrets.redact(&warning));
+    }
+
+    // Pin commands as compile would
+    let search_path = search_path(config.settings.as_ref(), None);
+    let (_, warnings) = pin_commands(&mut config, &search_path);
+    for warning in &warnings {
+        eprintln!("Warning: {}", secrets.redact(warning));
+    }
+
     // Determine target tools
     let target_tools: Vec<ToolName> = if tools.is_empty() {
         ToolName::concrete_tools()
@@ -75,12 +123,8 @@ pub fn diff_command(
         tools.iter().filter_map(|t| ToolName::from_str(t)).collect()
     };
 
-    // Get default targets from settings
-    let default_targets = config
-        .settings
-        .as_ref()
-        .map(|s| s.default_targets.clone())
-        .unwrap_or_default();
+    // Get transformation options from settings
+    let options = TransformOptions::from_settings(config.settings.as_ref());
 
     // Generate diff for each tool
     for tool in target_tools {
@@ -89,28 +133,41 @@ pub fn diff_command(
         }
 
         // Transform configuration
-        let new_content = match tool {
-            ToolName::Cursor => transform_for_cursor(&config.mcp.servers, &default_targets)
-                .map_err(MultiAgentError::TransformError)?,
-            ToolName::Opencode => transform_for_opencode(&config.mcp.servers, &default_targets)
-                .map_err(MultiAgentError::TransformError)?,
-            ToolName::Codex => transform_for_codex(&config.mcp.servers, &default_targets)
-                .map_err(MultiAgentError::TransformError)?,
-            ToolName::ClaudeCode => {
-                transform_for_claude_code(&config.mcp.servers, &default_targets)
-                    .map_err(MultiAgentError::TransformError)?
-            }
-            ToolName::All => continue,
-        };
+        if tool == ToolName::All {
+            continue;
+        }
+        let output = transform_for_tool(tool, &config.mcp.servers, &options)
+            .map_err(|e| MultiAgentError::TransformError(secrets.redact(&e)))?;
+        for warning in &output.warnings {
+            eprintln!("Warning: {}", secrets.redact(warning));
+        }
 
         let output_path = get_tool_config_path(tool);
+        print_file_diff(&tool.to_string(), &output_path, &output.content, &secrets);
+        for launcher in &output.launchers {
+            let label = format!("{tool} launcher for {}", launcher.server);
+            print_file_diff(&label, &launcher.path, &launcher.content, &secrets);
+        }
 
-        // Generate and display diff
-        let diff = generate_file_diff(&output_path, &new_content);
-
-        println!("=== {} ({}) ===", tool, output_path.display());
-        println!("{diff}");
-        println!();
+        // Claude Code permission rules live in its settings file
+        if tool == ToolName::ClaudeCode {
+            let state = StateTracker::load(&default_state_path()).map_err(|e| {
+                MultiAgentError::FileOpError(multi_agent_config::file_ops::writer::FileOpError::Io(
+                    e,
+                ))
+            })?;
+            if !output.permissions.is_empty() || !state.permissions().is_empty() {
+                let path = default_settings_path();
+                let existing = std::fs::read_to_string(&path).ok();
+                let content = merge_permissions(
+                    existing.as_deref(),
+                    &output.permissions,
+                    state.permissions(),
+                )
+                .map_err(MultiAgentError::TransformError)?;
+                print_file_diff("claude-code permissions", &path, &content, &secrets);
+            }
+        }
     }
 
     Ok(())
@@ -137,7 +194,7 @@ targets = ["cursor"]
         let mut temp_file = NamedTempFile::new().unwrap();
         temp_file.write_all(toml_content.as_bytes()).unwrap();
 
-        let result = diff_command(temp_file.path(), &[], false);
+        let result = diff_command(temp_file.path(), &[], false, false);
         assert!(result.is_ok());
     }
 
@@ -155,7 +212,7 @@ targets = ["all"]
         let mut temp_file = NamedTempFile::new().unwrap();
         temp_file.write_all(toml_content.as_bytes()).unwrap();
 
-        let result = diff_command(temp_file.path(), &["cursor".to_string()], false);
+        let result = diff_command(temp_file.path(), &["cursor".to_string()], false, false);
         assert!(result.is_ok());
     }
 
@@ -172,7 +229,7 @@ command = "npx"
         let mut temp_file = NamedTempFile::new().unwrap();
         temp_file.write_all(toml_content.as_bytes()).unwrap();
 
-        let result = diff_command(temp_file.path(), &[], false);
+        let result = diff_command(temp_file.path(), &[], false, false);
         assert!(result.is_err());
     }
 }
@@ -0,0 +1,138 @@
+//! Remove, enable, disable and set command implementations
+
+use multi_agent_config::{config::edit::ConfigEditor, error::MultiAgentError};
+use std::path::{Path, PathBuf};
+
+/// Remove a server from the config
+///
+/// # Errors
+///
+/// Returns error if the server does not exist or the config cannot be
+/// read, validated or written
+pub fn remove_command(
+    config_path: &Path,
+    name: &str,
+    verbose: bool,
+) -> Result<(), MultiAgentError> {
+    let backup = edit(config_path, |editor| editor.remove_server(name))?;
+    println!("Removed server '{name}' from {}", config_path.display());
+    print_backup(backup, verbose);
+    Ok(())
+}
+
+/// Enable or disable a server
+///
+/// # Errors
+///
+/// Returns error if the server does not exist or the config cannot be
+/// read, validated or written
+pub fn enable_command(
+    config_path: &Path,
+    name: &str,
+    enabled: bool,
+    verbose: bool,
+) -> Result<(), MultiAgentError> {
+    let backup = edit(config_path, |editor| editor.set_enabled(name, enabled))?;
+    let state = if enabled { "Enabled" } else { "Disabled" };
+    println!("{state} server '{name}' in {}", config_path.display());
+    print_backup(backup, verbose);
+    Ok(())
+}
+
+/// Set a field of a server
+///
+/// # Arguments
+///
+/// * `config_path` - Path to unified configuration file
+/// * `field` - `<server>.<field>`, e.g. `github.targets`
+/// * `value` - Value; lists such as `targets` may be comma-separated
+/// * `verbose` - Enable verbose output
+///
+/// # Errors
+///
+/// Returns error if no server matches the field, or the config cannot be
+/// read, validated or written
+pub fn set_command(
+    config_path: &Path,
+    field: &str,
+    value: &str,
+    verbose: bool,
+) -> Result<(), MultiAgentError> {
+    let mut set = None;
+    let backup = edit(config_path, |editor| {
+        set = Some(editor.set(field, value)?);
+        Ok(())
+    })?;
+    if let Some((server, field)) = set {
+        println!(
+            "Set {field} of server '{server}' in {}",
+            config_path.display()
+        );
+    }
+    print_backup(backup, verbose);
+    Ok(())
+}
+
+/// Open the config, apply an edit and save it
+fn edit(
+    config_path: &Path,
+    apply: impl FnOnce(&mut ConfigEditor) -> Result<(), String>,
+) -> Result<Option<PathBuf>, MultiAgentError> {
+    let mut editor = ConfigEditor::open(config_path)?;
+    apply(&mut editor).map_err(MultiAgentError::CliError)?;
+    editor.save()
+}
+
+/// Print where the previous config was backed up
+fn print_backup(backup: Option<PathBuf>, verbose: bool) {
+    if let (true, Some(backup)) = (verbose, backup) {
+        println!("  Created backup: {}", backup.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_edit_commands() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            r#"[settings]
+version = "1.0"
+
+[mcp.servers.myserver]
+command = "npx"
+
+[mcp.servers.other]
+url = "https://example.com/mcp"
+"#,
+        )
+        .unwrap();
+
+        enable_command(&config_path, "myserver", false, false).unwrap();
+        set_command(&config_path, "myserver.targets", "cursor,codex", false).unwrap();
+        assert!(set_command(&config_path, "myserver.command", "", false).is_err());
+        assert!(set_command(&config_path, "nope.command", "x", false).is_err());
+        remove_command(&config_path, "other", false).unwrap();
+        assert!(remove_command(&config_path, "other", false).is_err());
+        // The config needs at least one server
+        assert!(remove_command(&config_path, "myserver", false).is_err());
+
+        assert_eq!(
+            fs::read_to_string(&config_path).unwrap(),
+            r#"[settings]
+version = "1.0"
+
+[mcp.servers.myserver]
+command = "npx"
+enabled = false
+targets = ["cursor", "codex"]
+"#
+        );
+    }
+}
@@ -26,6 +26,7 @@ default_targets = ["cursor", "opencode", "codex"]
 
 # Example STDIO server
 [mcp.servers.example-stdio]
+transport = "stdio"
 command = "npx"
 args = ["-y", "@modelcontextprotocol/server-example"]
 enabled = true
@@ -37,6 +38,7 @@ targets = ["all"]
 
 # Example: Cursor-specific server with auto-approve
 # [mcp.servers.cursor-specific]
+# transport = "stdio"
 # command = "npx"
 # args = ["-y", "package"]
 # targets = ["cursor"]
@@ -45,14 +47,16 @@ targets = ["all"]
 
 # Example: Codex-specific server with timeouts
 # [mcp.servers.codex-specific]
+# transport = "stdio"
 # command = "node"
 # args = ["server.js"]
 # targets = ["codex"]
 # startup_timeout_sec = 30
 # tool_timeout_sec = 60
 
-# Example: HTTP server
+# Example: HTTP server ("http" for streamable HTTP, "sse" for SSE)
 # [mcp.servers.remote-server]
+# transport = "http"
 # url = "https://api.example.com/mcp"
 # bearer_token = "{API_TOKEN}"
 # targets = ["opencode", "codex", "claude-code"]
@@ -0,0 +1,120 @@
+//! Lock command implementation
+
+use multi_agent_config::{
+    config::{
+        package::{Lockfile, latest_version, lock_file_path, package_servers},
+        parse_and_expand_config,
+    },
+    error::MultiAgentError,
+    file_ops::write_file_atomic,
+};
+use std::path::Path;
+
+/// Record the version of every package in the lock file
+///
+/// Versions pinned in the config are recorded as they are. Other packages
+/// keep their locked version, or get the latest from their registry when
+/// they have none or `update` is set. Packages no longer in the config are
+/// dropped.
+///
+/// # Arguments
+///
+/// * `config_path` - Path to unified configuration file
+/// * `update` - Look up the latest version of unpinned packages again
+/// * `verbose` - Enable verbose output
+///
+/// # Returns
+///
+/// * `Ok(())` - Lock file is up to date
+/// * `Err(MultiAgentError)` - Error reading the config or resolving a package
+///
+/// # Errors
+///
+/// Returns error if the config or lock file cannot be read, a registry
+/// cannot be queried, or the lock file cannot be written
+pub fn lock_command(
+    config_path: &Path,
+    update: bool,
+    verbose: bool,
+) -> Result<(), MultiAgentError> {
+    let config = parse_and_expand_config(config_path)?;
+    let path = lock_file_path(config_path);
+    let old = Lockfile::load(&path).map_err(MultiAgentError::PackageError)?;
+
+    let mut lock = Lockfile::default();
+    for (name, spec) in package_servers(&config) {
+        let key = spec.key();
+        let locked = old.packages.get(&key).filter(|_| !update);
+        let version = if let Some(version) = spec.version.as_ref().or(locked) {
+            version.clone()
+        } else {
+            let version = latest_version(&spec).map_err(MultiAgentError::PackageError)?;
+            println!("Resolved {key} {version}");
+            version
+        };
+        if verbose {
+            println!("  {name}: {key}@{version}");
+        }
+        lock.packages.insert(key, version);
+    }
+
+    if lock == old && path.exists() {
+        println!("{} is up to date", path.display());
+        return Ok(());
+    }
+    write_file_atomic(&path, &lock.render(), Some(0o644))?;
+    println!(
+        "Locked {} package(s) in {}",
+        lock.packages.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_command_records_pinned_and_keeps_locked() {
+        let dir = TempDir::new().unwrap();
+        let config_path = dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            r#"
+[settings]
+version = "1.0"
+
+[mcp.servers.git]
+package = "pypi:mcp-server-git@0.6.2"
+
+[mcp.servers.github]
+package = "npm:@modelcontextprotocol/server-github"
+"#,
+        )
+        .unwrap();
+        let lock_path = lock_file_path(&config_path);
+        fs::write(
+            &lock_path,
+            "[packages]\n\"npm:@modelcontextprotocol/server-github\" = \"1.4.2\"\n\"npm:old\" = \
+             \"1.0.0\"\n",
+        )
+        .unwrap();
+
+        lock_command(&config_path, false, false).unwrap();
+
+        let lock = Lockfile::load(&lock_path).unwrap();
+        assert_eq!(
+            lock.packages.into_iter().collect::<Vec<_>>(),
+            [
+                (
+                    "npm:@modelcontextprotocol/server-github".to_string(),
+                    "1.4.2".to_string()
+                ),
+                ("pypi:mcp-server-git".to_string(), "0.6.2".to_string()),
+            ]
+        );
+    }
+}
@@ -1,11 +1,21 @@
 //! CLI command implementations
 
+pub mod add;
 pub mod compile;
 pub mod diff;
+pub mod edit;
 pub mod init;
+pub mod lock;
+pub mod secrets;
+pub mod status;
 pub mod validate;
 
+pub use add::{AddArgs, add_command, catalog_command};
 pub use compile::compile_command;
 pub use diff::diff_command;
+pub use edit::{enable_command, remove_command, set_command};
 pub use init::init_command;
-pub use validate::validate_command;
+pub use lock::lock_command;
+pub use secrets::{SecretsAction, secrets_command};
+pub use status::status_command;
+pub use validate::{DiagnosticFormat, validate_command};
@@ -0,0 +1,216 @@
+//! Secrets command implementation
+
+use clap::Subcommand;
+use multi_agent_config::{
+    error::MultiAgentError,
+    secrets::{
+        Kdf, SecretsError, SecretsFile, secrets_file_path,
+        store::{generate_key_file, key_file_path},
+    },
+};
+use std::{
+    io::{BufRead, Write},
+    path::Path,
+    process::Command,
+};
+
+/// Secrets subcommands
+#[derive(Subcommand)]
+pub enum SecretsAction {
+    /// Generate a new key file for encrypting the secrets file
+    Keygen {
+        /// Replace an existing key file (the old key is backed up)
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Open the decrypted secrets in $EDITOR and re-encrypt on save
+    Edit,
+
+    /// Set a secret (reads the value from stdin if omitted)
+    Set {
+        /// Secret name, referenced in the config as {NAME}
+        name: String,
+
+        /// Secret value
+        value: Option<String>,
+    },
+
+    /// Print a secret's value
+    Get {
+        /// Secret name
+        name: String,
+    },
+
+    /// Remove a secret
+    Remove {
+        /// Secret name
+        name: String,
+    },
+
+    /// List secret names
+    List,
+
+    /// Re-encrypt the secrets file under a new key
+    Rotate {
+        /// Switch to a passphrase read from `MULTI_AGENT_CONFIG_NEW_PASSPHRASE`
+        #[arg(long, conflicts_with = "key_file")]
+        passphrase: bool,
+
+        /// Switch to a newly generated key file
+        #[arg(long)]
+        key_file: bool,
+    },
+}
+
+/// Manage the encrypted secrets file next to the config
+///
+/// # Arguments
+///
+/// * `config_path` - Path to unified configuration file
+/// * `action` - Subcommand to run
+/// * `verbose` - Enable verbose output
+///
+/// # Returns
+///
+/// * `Ok(())` - Command completed
+/// * `Err(MultiAgentError)` - Error reading, decrypting or writing secrets
+///
+/// # Errors
+///
+/// Returns error if no key is available, the secrets file cannot be
+/// decrypted or written, or a requested secret does not exist
+pub fn secrets_command(
+    config_path: &Path,
+    action: SecretsAction,
+    verbose: bool,
+) -> Result<(), MultiAgentError> {
+    let path = secrets_file_path(config_path);
+
+    match action {
+        SecretsAction::Keygen { force } => {
+            let key_path = key_file_path();
+            generate_key_file(&key_path, force)?;
+            println!("Generated key file: {}", key_path.display());
+            println!("  Share it with your team out of band; never commit it");
+        }
+        SecretsAction::Edit => {
+            let mut secrets = SecretsFile::load_or_new(&path)?;
+            let edited = edit_in_editor(&secrets.to_plaintext())?;
+            secrets.replace_from_plaintext(&edited)?;
+            secrets.save()?;
+            println!(
+                "Saved {} secret(s) to {}",
+                secrets.values().len(),
+                path.display()
+            );
+        }
+        SecretsAction::Set { name, value } => {
+            let value = match value {
+                Some(value) => value,
+                None => read_value_from_stdin()?,
+            };
+            let mut secrets = SecretsFile::load_or_new(&path)?;
+            secrets.set(&name, value);
+            secrets.save()?;
+            if verbose {
+                println!("Set '{name}' in {}", path.display());
+            }
+        }
+        SecretsAction::Get { name } => {
+            let secrets = SecretsFile::load(&path)?;
+            let value = secrets
+                .get(&name)
+                .ok_or(SecretsError::UnknownSecret(name))?;
+            println!("{value}");
+        }
+        SecretsAction::Remove { name } => {
+            let mut secrets = SecretsFile::load(&path)?;
+            if !secrets.remove(&name) {
+                return Err(SecretsError::UnknownSecret(name).into());
+            }
+            secrets.save()?;
+            if verbose {
+                println!("Removed '{name}' from {}", path.display());
+            }
+        }
+        SecretsAction::List => {
+            let secrets = SecretsFile::load(&path)?;
+            for name in secrets.values().keys() {
+                println!("{name}");
+            }
+        }
+        SecretsAction::Rotate {
+            passphrase,
+            key_file,
+        } => {
+            let mut secrets = SecretsFile::load(&path)?;
+            let kdf = if passphrase {
+                Kdf::Pbkdf2Sha256
+            } else if key_file {
+                Kdf::KeyFile
+            } else {
+                secrets.kdf()
+            };
+            secrets.rotate(kdf)?;
+            match kdf {
+                Kdf::KeyFile => println!(
+                    "Rotated {} to a new key file: {}",
+                    path.display(),
+                    key_file_path().display()
+                ),
+                Kdf::Pbkdf2Sha256 => {
+                    println!("Rotated {} to the new passphrase", path.display());
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a secret value from the first line of stdin
+fn read_value_from_stdin() -> Result<String, MultiAgentError> {
+    let mut line = String::new();
+    std::io::stdin()
+        .lock()
+        .read_line(&mut line)
+        .map_err(SecretsError::Io)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Let the user edit text in `$VISUAL`, `$EDITOR` or `vi`
+///
+/// The decrypted text only ever lives in a private temporary file, which is
+/// removed afterwards.
+fn edit_in_editor(contents: &str) -> Result<String, MultiAgentError> {
+    let mut temp_file = tempfile::Builder::new()
+        .prefix("secrets")
+        .suffix(".toml")
+        .tempfile()
+        .map_err(SecretsError::Io)?;
+    temp_file
+        .write_all(contents.as_bytes())
+        .and_then(|()| temp_file.flush())
+        .map_err(SecretsError::Io)?;
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // Editors like "code --wait" carry their own arguments
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(parts)
+        .arg(temp_file.path())
+        .status()
+        .map_err(|e| MultiAgentError::CliError(format!("Cannot run editor '{editor}': {e}")))?;
+    if !status.success() {
+        return Err(MultiAgentError::CliError(format!(
+            "Editor '{editor}' exited with {status}; secrets left unchanged"
+        )));
+    }
+
+    std::fs::read_to_string(temp_file.path()).map_err(|e| SecretsError::Io(e).into())
+}
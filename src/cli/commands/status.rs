@@ -0,0 +1,95 @@
+//! Status command implementation
+
+use multi_agent_config::{
+    config::command::resolve_command,
+    error::MultiAgentError,
+    file_ops::{GeneratedFile, StateTracker, default_state_path, hash_file},
+};
+use std::ffi::OsStr;
+
+/// Whether a recorded file still matches what compile wrote
+fn file_status(file: &GeneratedFile) -> &'static str {
+    match hash_file(&file.path) {
+        Ok(hash) if hash == file.hash => "ok",
+        Ok(_) => "modified since compile",
+        Err(_) => "missing",
+    }
+}
+
+/// Show what the last compile wrote and whether it is still intact
+///
+/// Reports generated files and launcher scripts that were modified or
+/// removed since, and resolved commands whose executable no longer exists.
+///
+/// # Arguments
+///
+/// * `verbose` - Enable verbose output
+///
+/// # Returns
+///
+/// * `Ok(())` - Status displayed
+/// * `Err(MultiAgentError)` - State file cannot be read
+///
+/// # Errors
+///
+/// Returns error if the state file exists but cannot be read
+pub fn status_command(verbose: bool) -> Result<(), MultiAgentError> {
+    let state_path = default_state_path();
+    if verbose {
+        println!("State file: {}", state_path.display());
+    }
+    if !state_path.exists() {
+        println!("No configurations compiled yet");
+        return Ok(());
+    }
+
+    let tracker = StateTracker::load(&state_path).map_err(|e| {
+        MultiAgentError::FileOpError(multi_agent_config::file_ops::writer::FileOpError::Io(e))
+    })?;
+
+    println!("Last compile: {}", tracker.last_compile().to_rfc3339());
+
+    println!("Generated files:");
+    for file in tracker.generated_files() {
+        let status = file_status(file);
+        println!("  {} -> {} ({status})", file.tool, file.path.display());
+    }
+
+    if !tracker.launchers().is_empty() {
+        println!("Launchers:");
+        for launcher in tracker.launchers() {
+            let status = file_status(launcher);
+            println!(
+                "  {} -> {} ({status})",
+                launcher.tool,
+                launcher.path.display()
+            );
+        }
+    }
+
+    let resolved = tracker.resolved_commands();
+    if resolved.is_empty() {
+        return Ok(());
+    }
+
+    println!("Resolved commands:");
+    let mut stale = 0;
+    for pinned in resolved {
+        let path = pinned.path.to_string_lossy();
+        println!("  {}: {} -> {path}", pinned.server, pinned.command);
+        if let Err(e) = resolve_command(&path, OsStr::new("")) {
+            eprintln!(
+                "Warning: Server '{}': resolved command {}; run compile to resolve '{}' again",
+                pinned.server,
+                e.describe(OsStr::new("")),
+                pinned.command
+            );
+            stale += 1;
+        }
+    }
+    if stale > 0 {
+        println!("{stale} resolved command(s) no longer executable");
+    }
+
+    Ok(())
+}
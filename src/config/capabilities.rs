@@ -0,0 +1,353 @@
+//! What each tool's configuration format can express
+//!
+//! The validator consults this matrix to report servers that would be
+//! silently dropped or trimmed for a tool they target: an unsupported
+//! transport, a tool-specific field another tool ignores, or a server name
+//! the tool rejects.
+
+use crate::config::types::{ServerConfig, ToolName, Transport};
+
+/// Server fields, by config key, that only some tools emit
+pub const TOOL_SPECIFIC_FIELDS: &[&str] = &[
+    "disabled",
+    "autoApprove",
+    "startup_timeout_sec",
+    "tool_timeout_sec",
+    "cwd",
+    "tools.allow",
+    "tools.deny",
+    "tools.auto_approve",
+];
+
+/// Which server names a tool accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameRule {
+    /// Any non-empty name
+    Any,
+    /// ASCII letters, digits, `-` and `_`
+    Identifier,
+}
+
+impl NameRule {
+    /// Whether a server name satisfies the rule
+    #[must_use]
+    pub fn allows(self, name: &str) -> bool {
+        match self {
+            Self::Any => !name.is_empty(),
+            Self::Identifier => {
+                !name.is_empty()
+                    && name
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            }
+        }
+    }
+
+    /// Human-readable description of the rule
+    #[must_use]
+    pub const fn describe(self) -> &'static str {
+        match self {
+            Self::Any => "any non-empty name",
+            Self::Identifier => "letters, digits, '-' and '_' only",
+        }
+    }
+}
+
+/// What one tool's configuration format supports
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    /// Tool described
+    pub tool: ToolName,
+    /// Transports the tool can connect over
+    pub transports: &'static [Transport],
+    /// Which of [`TOOL_SPECIFIC_FIELDS`] the tool emits
+    pub fields: &'static [&'static str],
+    /// Server names the tool accepts
+    pub names: NameRule,
+    /// Longest server name the tool accepts, in characters
+    pub max_name_len: Option<usize>,
+    /// Keys the tool's server entries are generated with
+    pub output_fields: &'static [&'static str],
+}
+
+impl Capabilities {
+    /// Whether the tool can connect over a transport
+    #[must_use]
+    pub fn supports_transport(&self, transport: Transport) -> bool {
+        self.transports.contains(&transport)
+    }
+
+    /// Whether the tool emits a tool-specific field
+    #[must_use]
+    pub fn supports_field(&self, field: &str) -> bool {
+        self.fields.contains(&field)
+    }
+
+    /// Whether the tool accepts a server name
+    #[must_use]
+    pub fn allows_name(&self, name: &str) -> bool {
+        self.names.allows(name)
+            && self
+                .max_name_len
+                .is_none_or(|max| name.chars().count() <= max)
+    }
+
+    /// Human-readable description of the names the tool accepts
+    #[must_use]
+    pub fn describe_names(&self) -> String {
+        self.max_name_len.map_or_else(
+            || self.names.describe().to_string(),
+            |max| format!("{}, at most {max} characters", self.names.describe()),
+        )
+    }
+}
+
+/// Key two server names collide on: case, dots and spaces are ignored
+///
+/// Names that differ only this way are easily confused, and some tools fold
+/// them together (dots nest TOML tables, keys may be case-insensitive).
+#[must_use]
+pub fn normalize_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '.' | ' ' => '_',
+            c => c.to_ascii_lowercase(),
+        })
+        .collect()
+}
+
+/// Capability matrix, one entry per concrete tool
+pub const MATRIX: &[Capabilities] = &[
+    Capabilities {
+        tool: ToolName::ClaudeCode,
+        transports: &[Transport::Stdio, Transport::Sse, Transport::Http],
+        fields: &["tools.deny", "tools.auto_approve"],
+        names: NameRule::Identifier,
+        max_name_len: Some(64),
+        output_fields: &["type", "command", "env", "enabled", "url", "headers"],
+    },
+    Capabilities {
+        tool: ToolName::Cursor,
+        transports: &[Transport::Stdio],
+        fields: &["disabled", "autoApprove", "tools.auto_approve"],
+        names: NameRule::Any,
+        max_name_len: None,
+        output_fields: &["command", "args", "env", "disabled", "autoApprove"],
+    },
+    Capabilities {
+        tool: ToolName::Opencode,
+        transports: &[Transport::Stdio, Transport::Sse, Transport::Http],
+        fields: &["startup_timeout_sec", "tools.allow", "tools.deny"],
+        names: NameRule::Any,
+        max_name_len: None,
+        output_fields: &[
+            "type", "command", "env", "enabled", "url", "headers", "timeout",
+        ],
+    },
+    Capabilities {
+        tool: ToolName::Codex,
+        transports: &[Transport::Stdio, Transport::Http],
+        fields: &[
+            "startup_timeout_sec",
+            "tool_timeout_sec",
+            "cwd",
+            "tools.allow",
+            "tools.deny",
+        ],
+        names: NameRule::Identifier,
+        max_name_len: Some(64),
+        output_fields: &[
+            "command",
+            "args",
+            "cwd",
+            "startup_timeout_sec",
+            "tool_timeout_sec",
+            "env_vars",
+            "env",
+            "url",
+            "bearer_token",
+            "bearer_token_env_var",
+            "http_headers",
+            "env_http_headers",
+            "enabled_tools",
+            "disabled_tools",
+        ],
+    },
+];
+
+/// Capabilities of a concrete tool; `None` for `all`
+#[must_use]
+pub fn capabilities(tool: ToolName) -> Option<&'static Capabilities> {
+    MATRIX.iter().find(|caps| caps.tool == tool)
+}
+
+/// Timeout fields, which both server kinds have
+pub const TIMEOUT_FIELDS: &[&str] = &["startup_timeout_sec", "tool_timeout_sec"];
+
+/// Fields reported for every tool that ignores them
+///
+/// Reported even when the server doesn't pick its tools: an allow or deny
+/// list that isn't enforced exposes tools, and an ignored `cwd` starts the
+/// server in the wrong place.
+pub const ALWAYS_REPORTED_FIELDS: &[&str] = &["cwd", "tools.allow", "tools.deny"];
+
+/// Tool-specific fields a server sets
+#[must_use]
+pub fn tool_specific_fields(server: &ServerConfig) -> Vec<&'static str> {
+    let stdio = match server {
+        ServerConfig::Stdio(stdio) => Some(stdio),
+        ServerConfig::Http(_) => None,
+    };
+
+    [
+        ("disabled", stdio.is_some_and(|s| s.disabled.is_some())),
+        (
+            "autoApprove",
+            stdio.is_some_and(|s| s.auto_approve.is_some()),
+        ),
+        (
+            "startup_timeout_sec",
+            server.startup_timeout_sec().is_some(),
+        ),
+        ("tool_timeout_sec", server.tool_timeout_sec().is_some()),
+        ("cwd", stdio.is_some_and(|s| s.cwd.is_some())),
+        ("tools.allow", !server.tools().allow.is_empty()),
+        ("tools.deny", !server.tools().deny.is_empty()),
+        (
+            "tools.auto_approve",
+            !server.tools().auto_approve.is_empty(),
+        ),
+    ]
+    .into_iter()
+    .filter_map(|(field, set)| set.then_some(field))
+    .collect()
+}
+
+/// Tools a server lists by name in `targets`, if it lists any
+///
+/// `None` when the server targets `all` or falls back to the default
+/// targets, i.e. when the user did not pick tools for this server.
+#[must_use]
+pub fn explicit_targets(server: &ServerConfig) -> Option<Vec<ToolName>> {
+    let targets = server.targets();
+    if targets.is_empty() || targets.iter().any(|t| t == "all") {
+        return None;
+    }
+    Some(
+        targets
+            .iter()
+            .filter_map(|t| ToolName::from_str(t))
+            .collect(),
+    )
+}
+
+/// Tools a server is compiled for, before transport filtering
+///
+/// Mirrors target filtering: the server's targets, falling back to the
+/// default targets, with `all` meaning every tool.
+#[must_use]
+pub fn target_tools(server: &ServerConfig, default_targets: &[String]) -> Vec<ToolName> {
+    let targets = server.targets();
+    let targets = if targets.is_empty() || targets == ["all"] {
+        default_targets
+    } else {
+        targets
+    };
+
+    if targets.is_empty() || targets.iter().any(|t| t == "all") {
+        return ToolName::concrete_tools();
+    }
+    targets
+        .iter()
+        .filter_map(|t| ToolName::from_str(t))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::{HttpServerConfig, StdioServerConfig};
+
+    #[test]
+    fn test_matrix_covers_every_tool() {
+        for tool in ToolName::concrete_tools() {
+            let caps = capabilities(tool).unwrap();
+            assert!(caps.supports_transport(Transport::Stdio));
+            assert!(caps.fields.iter().all(|f| TOOL_SPECIFIC_FIELDS.contains(f)));
+            let unique: std::collections::BTreeSet<_> = caps.output_fields.iter().collect();
+            assert_eq!(unique.len(), caps.output_fields.len(), "{tool}");
+        }
+        assert!(capabilities(ToolName::All).is_none());
+        assert!(
+            !capabilities(ToolName::Cursor)
+                .unwrap()
+                .supports_transport(Transport::Http)
+        );
+        assert!(
+            !capabilities(ToolName::Codex)
+                .unwrap()
+                .supports_transport(Transport::Sse)
+        );
+    }
+
+    #[test]
+    fn test_name_rules() {
+        assert!(NameRule::Identifier.allows("github_mcp-2"));
+        assert!(!NameRule::Identifier.allows("my server"));
+        assert!(!NameRule::Identifier.allows("a.b"));
+        assert!(NameRule::Any.allows("my server"));
+        assert!(!NameRule::Any.allows(""));
+
+        let codex = capabilities(ToolName::Codex).unwrap();
+        assert!(codex.allows_name(&"a".repeat(64)));
+        assert!(!codex.allows_name(&"a".repeat(65)));
+        assert_eq!(
+            codex.describe_names(),
+            "letters, digits, '-' and '_' only, at most 64 characters"
+        );
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(normalize_name("GitHub"), normalize_name("github"));
+        assert_eq!(normalize_name("my.server"), normalize_name("My Server"));
+        assert_ne!(normalize_name("my-server"), normalize_name("my_server"));
+    }
+
+    #[test]
+    fn test_target_tools() {
+        let server = ServerConfig::Stdio(StdioServerConfig {
+            command: "npx".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(target_tools(&server, &[]), ToolName::concrete_tools());
+        assert_eq!(
+            target_tools(&server, &["codex".to_string()]),
+            vec![ToolName::Codex]
+        );
+        assert_eq!(explicit_targets(&server), None);
+
+        let server = ServerConfig::Stdio(StdioServerConfig {
+            command: "npx".to_string(),
+            targets: vec!["cursor".to_string()],
+            ..Default::default()
+        });
+        assert_eq!(
+            target_tools(&server, &["codex".to_string()]),
+            vec![ToolName::Cursor]
+        );
+    }
+    #[test]
+    fn test_tool_specific_fields_include_http_timeouts() {
+        let server = ServerConfig::Http(HttpServerConfig {
+            url: "https://example.com/mcp".to_string(),
+            tool_timeout_sec: Some(120),
+            ..Default::default()
+        });
+        assert_eq!(tool_specific_fields(&server), ["tool_timeout_sec"]);
+
+        let opencode = capabilities(ToolName::Opencode).unwrap();
+        assert!(opencode.supports_field("startup_timeout_sec"));
+        assert!(!opencode.supports_field("tool_timeout_sec"));
+    }
+}
@@ -0,0 +1,150 @@
+//! Stable diagnostic codes
+//!
+//! Every validation finding carries a code that does not change between
+//! releases, so it can be matched in CI or suppressed per server with
+//! `allow`. The letter is the default severity: `E` for errors, `W` for
+//! warnings and `I` for info.
+
+use crate::diagnostics::Severity;
+
+/// Settings version is not a semver string
+pub const INVALID_VERSION: &str = "E001";
+/// Settings version is not supported
+pub const UNSUPPORTED_VERSION: &str = "E002";
+/// Target is not a known tool
+pub const INVALID_TARGET: &str = "E003";
+/// No servers are defined
+pub const NO_SERVERS: &str = "E004";
+/// STDIO server command is empty
+pub const EMPTY_COMMAND: &str = "E005";
+/// HTTP server URL has no http(s) scheme
+pub const INVALID_URL: &str = "E006";
+/// Both `bearer_token` and `auth` are set
+pub const CONFLICTING_AUTH: &str = "E007";
+/// Header name is not a valid HTTP token
+pub const INVALID_HEADER_NAME: &str = "E008";
+/// A header is set by both `headers` and authentication
+pub const DUPLICATE_AUTH_HEADER: &str = "E009";
+/// Transport does not match the server's `command` or `url`
+pub const TRANSPORT_MISMATCH: &str = "E010";
+/// An explicitly targeted tool lacks the server's transport
+pub const UNSUPPORTED_TRANSPORT: &str = "E011";
+/// Key is not part of the schema
+pub const UNKNOWN_KEY: &str = "E012";
+/// Two servers are emitted under the same name for a tool
+pub const DUPLICATE_SERVER_NAME: &str = "E013";
+/// `aliases` names an unknown tool or is empty
+pub const INVALID_ALIAS: &str = "E014";
+/// `overrides` names an unknown tool or a field the server kind lacks
+pub const INVALID_OVERRIDE: &str = "E015";
+/// `extra` names an unknown tool
+pub const INVALID_EXTRA: &str = "E016";
+/// A `tools` list has an empty name or contradicts another list
+pub const INVALID_TOOL_POLICY: &str = "E017";
+/// A container has no image or a malformed mount
+pub const INVALID_CONTAINER: &str = "E018";
+/// `package` is not a valid `<manager>:<name>[@<version>]` shorthand
+pub const INVALID_PACKAGE: &str = "E019";
+/// Target is listed more than once
+pub const DUPLICATE_TARGET: &str = "W001";
+/// Literal value looks like a credential
+pub const HARDCODED_CREDENTIAL: &str = "W002";
+/// `allow` lists a code that is unknown or an error
+pub const INVALID_ALLOW: &str = "W003";
+/// A targeted tool lacks the server's inferred transport
+pub const UNREACHABLE_TARGET: &str = "W004";
+/// A tool-specific field is ignored by a targeted tool
+pub const IGNORED_FIELD: &str = "W005";
+/// A tool the server is compiled for rejects its name
+pub const INVALID_NAME_FOR_TOOL: &str = "W006";
+/// A STDIO server's command does not resolve to an executable
+pub const MISSING_EXECUTABLE: &str = "W007";
+/// Server names differ only by case, dots or spaces
+pub const SIMILAR_SERVER_NAMES: &str = "W008";
+/// An `extra` key replaces a field the tool entry is generated with
+pub const EXTRA_SHADOWS_FIELD: &str = "W009";
+/// A STDIO server's working directory is missing or relative
+pub const MISSING_WORKING_DIR: &str = "W010";
+/// A package has no version in the config or the lock file
+pub const UNPINNED_PACKAGE: &str = "W011";
+/// A file that may hold secrets is open to, or owned by, another user
+pub const INSECURE_PERMISSIONS: &str = "W012";
+/// A server has no `transport` and relies on the deprecated inference
+pub const INFERRED_TRANSPORT: &str = "W013";
+/// Server is disabled and will not be compiled
+pub const SERVER_DISABLED: &str = "I001";
+/// A timeout is ignored by some of the tools a server is compiled for
+pub const IGNORED_TIMEOUT: &str = "I002";
+
+/// Every code with a short description, in order
+pub const ALL: &[(&str, &str)] = &[
+    (INVALID_VERSION, "invalid settings version"),
+    (UNSUPPORTED_VERSION, "unsupported settings version"),
+    (INVALID_TARGET, "unknown target tool"),
+    (NO_SERVERS, "no servers defined"),
+    (EMPTY_COMMAND, "empty command"),
+    (INVALID_URL, "URL without http(s) scheme"),
+    (CONFLICTING_AUTH, "both bearer_token and auth"),
+    (INVALID_HEADER_NAME, "invalid header name"),
+    (DUPLICATE_AUTH_HEADER, "header set twice"),
+    (TRANSPORT_MISMATCH, "transport does not match command/url"),
+    (UNSUPPORTED_TRANSPORT, "target lacks transport"),
+    (UNKNOWN_KEY, "unknown key"),
+    (DUPLICATE_SERVER_NAME, "duplicate server name for a tool"),
+    (INVALID_ALIAS, "invalid alias"),
+    (INVALID_OVERRIDE, "invalid override"),
+    (INVALID_EXTRA, "invalid extra"),
+    (INVALID_TOOL_POLICY, "invalid tools list"),
+    (INVALID_CONTAINER, "invalid container"),
+    (INVALID_PACKAGE, "invalid package"),
+    (DUPLICATE_TARGET, "duplicate target"),
+    (HARDCODED_CREDENTIAL, "hard-coded credential"),
+    (INVALID_ALLOW, "invalid allow entry"),
+    (UNREACHABLE_TARGET, "target lacks inferred transport"),
+    (IGNORED_FIELD, "field ignored by target"),
+    (INVALID_NAME_FOR_TOOL, "name rejected by tool"),
+    (MISSING_EXECUTABLE, "command not executable"),
+    (SIMILAR_SERVER_NAMES, "names collide after normalisation"),
+    (EXTRA_SHADOWS_FIELD, "extra key shadows a generated field"),
+    (MISSING_WORKING_DIR, "working directory missing"),
+    (UNPINNED_PACKAGE, "package version not pinned"),
+    (INSECURE_PERMISSIONS, "secret file not private"),
+    (INFERRED_TRANSPORT, "transport inferred (deprecated)"),
+    (SERVER_DISABLED, "server disabled"),
+    (IGNORED_TIMEOUT, "timeout ignored by some targets"),
+];
+
+/// Default severity of a code, from its letter
+#[must_use]
+pub const fn default_severity(code: &str) -> Severity {
+    match code.as_bytes().first() {
+        Some(b'W') => Severity::Warning,
+        Some(b'I') => Severity::Info,
+        _ => Severity::Error,
+    }
+}
+
+/// Whether a code is one of [`ALL`]
+#[must_use]
+pub fn is_known(code: &str) -> bool {
+    ALL.iter().any(|(known, _)| *known == code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_codes_are_unique_and_well_formed() {
+        let mut seen = HashSet::new();
+        for (code, _) in ALL {
+            assert!(seen.insert(code), "duplicate code {code}");
+            assert_eq!(code.len(), 4);
+            assert!(code[1..].chars().all(|c| c.is_ascii_digit()));
+        }
+        assert_eq!(default_severity(DUPLICATE_TARGET), Severity::Warning);
+        assert_eq!(default_severity(SERVER_DISABLED), Severity::Info);
+        assert_eq!(default_severity(UNKNOWN_KEY), Severity::Error);
+    }
+}
@@ -0,0 +1,326 @@
+//! Resolution of STDIO server commands to executables
+//!
+//! Bare command names are looked up on a search path, paths containing a
+//! separator are checked directly (relative to the current directory), and
+//! a leading `~` is expanded to the home directory.
+//!
+//! Servers with `resolve_command` are pinned to the resolved path at compile
+//! time, for tools launched with a reduced `PATH`.
+
+use crate::config::types::{MultiAgentConfig, ServerConfig, Settings};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::{OsStr, OsString},
+    path::{MAIN_SEPARATOR, Path, PathBuf},
+};
+
+/// Why a command could not be resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// No executable with this name on the search path
+    NotFound,
+    /// The path does not exist
+    Missing(PathBuf),
+    /// The path exists but is not an executable file
+    NotExecutable(PathBuf),
+}
+
+impl ResolveError {
+    /// Why the command can't be run, e.g. "not found on search path /usr/bin"
+    #[must_use]
+    pub fn describe(&self, search_path: &OsStr) -> String {
+        match self {
+            Self::NotFound => format!("not found on search path {}", search_path.to_string_lossy()),
+            Self::Missing(path) => format!("does not exist ({})", path.display()),
+            Self::NotExecutable(path) => format!("is not executable ({})", path.display()),
+        }
+    }
+}
+
+/// A server command pinned to the executable it resolved to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResolvedCommand {
+    /// Server name
+    pub server: String,
+
+    /// Command as written in the config
+    pub command: String,
+
+    /// Absolute path emitted instead
+    pub path: PathBuf,
+}
+
+/// Search path for resolving commands
+///
+/// An explicit override wins, then `search_path` in `[settings]`, then the
+/// current `PATH`.
+///
+/// # Arguments
+///
+/// * `settings` - Settings section, if any
+/// * `override_path` - Search path given on the command line, if any
+#[must_use]
+pub fn search_path(settings: Option<&Settings>, override_path: Option<&str>) -> OsString {
+    override_path
+        .or_else(|| settings.and_then(|s| s.search_path.as_deref()))
+        .map_or_else(|| env::var_os("PATH").unwrap_or_default(), OsString::from)
+}
+
+/// Expand a leading `~` to the home directory
+#[must_use]
+pub fn expand_tilde(command: &str) -> PathBuf {
+    let home = || dirs::home_dir().unwrap_or_default();
+    if command == "~" {
+        home()
+    } else if let Some(rest) = command
+        .strip_prefix("~/")
+        .or_else(|| command.strip_prefix(&format!("~{MAIN_SEPARATOR}")))
+    {
+        home().join(rest)
+    } else {
+        PathBuf::from(command)
+    }
+}
+
+/// Whether a command is a path rather than a bare name
+#[must_use]
+pub fn is_path(command: &str) -> bool {
+    command.starts_with('~') || command.contains('/') || command.contains(MAIN_SEPARATOR)
+}
+
+/// Resolve a command to the executable that would run
+///
+/// # Arguments
+///
+/// * `command` - Command as written in the config
+/// * `search_path` - `PATH`-style list of directories for bare names
+///
+/// # Returns
+///
+/// * `Ok(PathBuf)` - Absolute path of the executable
+/// * `Err(ResolveError)` - Why the command can't be run
+///
+/// # Errors
+///
+/// Returns an error if a bare name is not on the search path, or a path
+/// does not exist or is not executable
+pub fn resolve_command(command: &str, search_path: &OsStr) -> Result<PathBuf, ResolveError> {
+    let cwd = env::current_dir().unwrap_or_default();
+
+    if !is_path(command) {
+        return which::which_in(command, Some(search_path), &cwd)
+            .map_err(|_| ResolveError::NotFound);
+    }
+
+    let path = cwd.join(expand_tilde(command));
+    if !path.exists() {
+        return Err(ResolveError::Missing(path));
+    }
+    which::which_in(&path, None::<&OsStr>, Path::new("/"))
+        .map_err(|_| ResolveError::NotExecutable(path))
+}
+
+/// Rewrite the commands of servers with `resolve_command` to absolute paths
+///
+/// A server's `resolve_command` overrides the setting. Disabled servers and
+/// commands with runtime references (`${...}`) are left alone. With
+/// `inject_path` set, servers without their own `PATH` also get the search
+/// path as `PATH`, so child processes find their interpreters.
+///
+/// # Arguments
+///
+/// * `config` - Expanded configuration, rewritten in place
+/// * `search_path` - `PATH`-style list of directories for bare names
+///
+/// # Returns
+///
+/// The commands that were pinned, and a warning for each that could not be
+/// resolved and was left unchanged
+pub fn pin_commands(
+    config: &mut MultiAgentConfig,
+    search_path: &OsStr,
+) -> (Vec<ResolvedCommand>, Vec<String>) {
+    let (default, inject_path) = config
+        .settings
+        .as_ref()
+        .map_or((false, false), |s| (s.resolve_command, s.inject_path));
+
+    let mut resolved = Vec::new();
+    let mut warnings = Vec::new();
+    let sorted: BTreeMap<_, _> = config.mcp.servers.iter_mut().collect();
+    for (name, server) in sorted {
+        let ServerConfig::Stdio(stdio) = server else {
+            continue;
+        };
+        if !stdio.enabled
+            || stdio.container.is_some()
+            || !stdio.resolve_command.unwrap_or(default)
+            || stdio.command.contains("${")
+        {
+            continue;
+        }
+
+        match resolve_command(&stdio.command, search_path) {
+            Ok(path) => {
+                resolved.push(ResolvedCommand {
+                    server: name.clone(),
+                    command: stdio.command.clone(),
+                    path: path.clone(),
+                });
+                stdio.command = path.to_string_lossy().into_owned();
+                if inject_path {
+                    stdio
+                        .env
+                        .get_or_insert_with(Default::default)
+                        .entry("PATH".to_string())
+                        .or_insert_with(|| search_path.to_string_lossy().into_owned());
+                }
+            }
+            Err(e) => warnings.push(format!(
+                "Server '{name}': command '{}' {}; emitting it unresolved",
+                stdio.command,
+                e.describe(search_path)
+            )),
+        }
+    }
+    (resolved, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_bare_name_on_search_path() {
+        let dir = TempDir::new().unwrap();
+        let tool = dir.path().join("my-tool");
+        fs::write(&tool, "#!/bin/sh\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let search = dir.path().as_os_str();
+        assert_eq!(resolve_command("my-tool", search), Ok(tool.clone()));
+        assert_eq!(
+            resolve_command("other-tool", search),
+            Err(ResolveError::NotFound)
+        );
+        assert_eq!(
+            resolve_command(tool.to_str().unwrap(), OsStr::new("")),
+            Ok(tool)
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_resolve_path_not_executable() {
+        let dir = TempDir::new().unwrap();
+        let script = dir.path().join("script.sh");
+        fs::write(&script, "echo\n").unwrap();
+
+        assert_eq!(
+            resolve_command(script.to_str().unwrap(), OsStr::new("")),
+            Err(ResolveError::NotExecutable(script))
+        );
+
+        let missing = dir.path().join("missing");
+        assert_eq!(
+            resolve_command(missing.to_str().unwrap(), OsStr::new("")),
+            Err(ResolveError::Missing(missing))
+        );
+    }
+
+    #[test]
+    fn test_search_path_precedence() {
+        let settings = Settings {
+            search_path: Some("/opt/bin".to_string()),
+            ..Settings::default()
+        };
+        assert_eq!(
+            search_path(Some(&settings), Some("/usr/bin")),
+            OsString::from("/usr/bin")
+        );
+        assert_eq!(
+            search_path(Some(&settings), None),
+            OsString::from("/opt/bin")
+        );
+    }
+
+    #[test]
+    fn test_expand_tilde() {
+        let home = dirs::home_dir().unwrap_or_default();
+        assert_eq!(expand_tilde("~/bin/tool"), home.join("bin/tool"));
+        assert_eq!(
+            expand_tilde("/usr/bin/tool"),
+            PathBuf::from("/usr/bin/tool")
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_pin_commands() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let tool = dir.path().join("npx");
+        fs::write(&tool, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&tool, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut config: MultiAgentConfig = toml::from_str(
+            r#"
+[settings]
+version = "1.0"
+resolve_command = true
+inject_path = true
+
+[mcp.servers.pinned]
+command = "npx"
+
+[mcp.servers.unpinned]
+command = "npx"
+resolve_command = false
+
+[mcp.servers.missing]
+command = "uvx"
+"#,
+        )
+        .unwrap();
+
+        let (resolved, warnings) = pin_commands(&mut config, dir.path().as_os_str());
+        assert_eq!(
+            resolved,
+            vec![ResolvedCommand {
+                server: "pinned".to_string(),
+                command: "npx".to_string(),
+                path: tool.clone(),
+            }]
+        );
+        assert_eq!(
+            warnings,
+            vec![format!(
+                "Server 'missing': command 'uvx' not found on search path {}; emitting it \
+                 unresolved",
+                dir.path().display()
+            )]
+        );
+
+        let ServerConfig::Stdio(pinned) = &config.mcp.servers["pinned"] else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(pinned.command, tool.to_string_lossy());
+        assert_eq!(
+            pinned.env.as_ref().unwrap()["PATH"],
+            dir.path().to_string_lossy()
+        );
+        let ServerConfig::Stdio(unpinned) = &config.mcp.servers["unpinned"] else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(unpinned.command, "npx");
+    }
+}
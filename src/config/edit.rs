@@ -0,0 +1,398 @@
+//! In-place edits of the unified config
+//!
+//! The file is edited as a TOML document, so comments, ordering and
+//! formatting the user wrote are kept. Every edit is validated before it is
+//! saved, and the previous file is backed up like generated files are. Only
+//! errors the edit introduces block saving: the raw file can fail checks
+//! that pass once `{VAR}` references are expanded.
+//!
+//! Fields are addressed as `<server>.<field>`, e.g. `github.targets` or
+//! `github.env.GITHUB_TOKEN`. Values given as text are read as TOML when
+//! they are booleans, integers, arrays or inline tables, as comma-separated
+//! lists for list fields such as `targets`, and as strings otherwise; values
+//! under `env` and `headers` are always strings.
+
+use crate::{
+    config::{parser::read_file_utf8, types::MultiAgentConfig, validator::validate_config},
+    diagnostics::SourceFile,
+    error::{ConfigError, MultiAgentError},
+    file_ops::{create_backup, write_file_atomic, writer::FileOpError},
+};
+use std::path::{Path, PathBuf};
+use toml_edit::{DocumentMut, InlineTable, Item, Table, TableLike, Value};
+
+/// Fields holding lists of strings, set from comma-separated text
+const LIST_FIELDS: &[&str] = &[
+    "args",
+    "targets",
+    "autoApprove",
+    "allow",
+    "deny",
+    "auto_approve",
+    "mounts",
+];
+
+/// Tables whose values are always strings
+const STRING_TABLES: &[&str] = &["env", "headers"];
+
+/// The unified config, opened for editing
+#[derive(Debug)]
+pub struct ConfigEditor {
+    /// File the config is read from and saved to
+    path: PathBuf,
+    /// Document being edited
+    document: DocumentMut,
+    /// Text of the file when it was opened
+    original: String,
+}
+
+impl ConfigEditor {
+    /// Open a config file for editing
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not valid TOML
+    pub fn open(path: &Path) -> Result<Self, ConfigError> {
+        let text = read_file_utf8(path)?;
+        let document = text.parse::<DocumentMut>().map_err(|e| {
+            e.span().map_or_else(
+                || ConfigError::parse_error(e.message(), 0),
+                |span| {
+                    let source = SourceFile::new(path, text.as_str());
+                    ConfigError::parse_error_at(e.message(), &source, span)
+                },
+            )
+        })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            document,
+            original: text,
+        })
+    }
+
+    /// Current text of the config
+    #[must_use]
+    pub fn content(&self) -> String {
+        self.document.to_string()
+    }
+
+    /// Whether `[mcp.servers]` has a server
+    #[must_use]
+    pub fn has_server(&self, name: &str) -> bool {
+        self.document
+            .get("mcp")
+            .and_then(|mcp| mcp.get("servers"))
+            .and_then(|servers| servers.get(name))
+            .is_some()
+    }
+
+    /// Add a server as a `[mcp.servers.<name>]` table at the end of the file
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the server exists or `mcp.servers` is not a table
+    pub fn add_server(&mut self, name: &str, server: Table) -> Result<(), String> {
+        if self.has_server(name) {
+            return Err(format!("Server '{name}' already exists"));
+        }
+        let servers = self.servers_mut()?;
+        servers.insert(name, Item::Table(server));
+        Ok(())
+    }
+
+    /// Remove a server
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the server does not exist
+    pub fn remove_server(&mut self, name: &str) -> Result<(), String> {
+        self.servers_mut()?
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| format!("No server '{name}'"))
+    }
+
+    /// Enable or disable a server
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the server does not exist
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        self.set_value(name, &["enabled"], Value::from(enabled))
+    }
+
+    /// Set a field from its command-line text
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - `<server>.<field>`, with nested fields separated by `.`
+    /// * `text` - Value, read as described in the module documentation
+    ///
+    /// # Returns
+    ///
+    /// The server name and the field path within it
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if no server matches the path, there is no field
+    /// after the server name, or a parent of the field is not a table
+    pub fn set(&mut self, path: &str, text: &str) -> Result<(String, String), String> {
+        let (server, field) = self.split_path(path)?;
+        let keys: Vec<&str> = field.split('.').collect();
+        let value = parse_value(&keys, text);
+        self.set_value(&server, &keys, value)?;
+        Ok((server, field))
+    }
+
+    /// Split `<server>.<field>` at the longest server name that exists, so
+    /// server names may contain dots
+    fn split_path(&self, path: &str) -> Result<(String, String), String> {
+        let servers = self
+            .document
+            .get("mcp")
+            .and_then(|mcp| mcp.get("servers"))
+            .and_then(Item::as_table_like);
+        path.match_indices('.')
+            .rev()
+            .map(|(dot, _)| (&path[..dot], &path[dot + 1..]))
+            .find(|(server, field)| {
+                !field.is_empty() && servers.is_some_and(|servers| servers.contains_key(server))
+            })
+            .map(|(server, field)| (server.to_string(), field.to_string()))
+            .ok_or_else(|| format!("'{path}' is not <server>.<field> for an existing server"))
+    }
+
+    /// Set a field of a server, creating parent tables as inline tables
+    ///
+    /// A replaced value keeps its surrounding whitespace and comment.
+    fn set_value(&mut self, server: &str, keys: &[&str], mut value: Value) -> Result<(), String> {
+        let Some((last, parents)) = keys.split_last() else {
+            return Err("No field given".to_string());
+        };
+        let mut table: &mut dyn TableLike = self
+            .servers_mut()?
+            .get_mut(server)
+            .and_then(Item::as_table_like_mut)
+            .ok_or_else(|| format!("No server '{server}'"))?;
+        for key in parents {
+            if !table.contains_key(key) {
+                table.insert(key, Item::Value(Value::InlineTable(InlineTable::new())));
+            }
+            table = table
+                .get_mut(key)
+                .and_then(Item::as_table_like_mut)
+                .ok_or_else(|| format!("'{key}' in server '{server}' is not a table"))?;
+        }
+
+        if let Some(old) = table.get(last).and_then(Item::as_value) {
+            *value.decor_mut() = old.decor().clone();
+        }
+        table.insert(last, Item::Value(value));
+        Ok(())
+    }
+
+    /// The `[mcp.servers]` table, created if missing
+    fn servers_mut(&mut self) -> Result<&mut Table, String> {
+        let implicit = || {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        };
+        self.document
+            .entry("mcp")
+            .or_insert_with(implicit)
+            .as_table_mut()
+            .ok_or_else(|| "'mcp' is not a table".to_string())?
+            .entry("servers")
+            .or_insert_with(implicit)
+            .as_table_mut()
+            .ok_or_else(|| "'mcp.servers' is not a table".to_string())
+    }
+
+    /// Validate the edited config and write it, backing up the old file
+    ///
+    /// # Returns
+    ///
+    /// Where the previous file was backed up
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the edited config does not parse or has
+    /// validation errors the file did not have before, or it cannot be
+    /// written
+    pub fn save(&self) -> Result<Option<PathBuf>, MultiAgentError> {
+        let content = self.content();
+        let config: MultiAgentConfig = toml::from_str(&content)
+            .map_err(|e| ConfigError::ValidationError(e.message().to_string()))?;
+
+        let mut existing = toml::from_str::<MultiAgentConfig>(&self.original)
+            .map_or_else(|_| Vec::new(), |original| error_messages(&original));
+        let introduced: Vec<String> = error_messages(&config)
+            .into_iter()
+            .filter(|message| {
+                existing
+                    .iter()
+                    .position(|old| old == message)
+                    .map(|at| existing.swap_remove(at))
+                    .is_none()
+            })
+            .collect();
+        if !introduced.is_empty() {
+            return Err(ConfigError::ValidationError(introduced.join("; ")).into());
+        }
+
+        let backup = create_backup(&self.path).map_err(FileOpError::Io)?;
+        write_file_atomic(&self.path, &content, Some(0o600))?;
+        Ok(backup)
+    }
+}
+
+/// Validation errors of a config, as messages
+fn error_messages(config: &MultiAgentConfig) -> Vec<String> {
+    validate_config(config)
+        .err()
+        .unwrap_or_default()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// Value for a field from its command-line text
+fn parse_value(keys: &[&str], text: &str) -> Value {
+    let in_string_table = keys.len() > 1 && STRING_TABLES.contains(&keys[keys.len() - 2]);
+    if in_string_table {
+        return Value::from(text);
+    }
+    if let Ok(mut value) = text.parse::<Value>() {
+        if !value.is_str() && !value.is_float() && !value.is_datetime() {
+            value.decor_mut().clear();
+            return value;
+        }
+    }
+    if keys.last().is_some_and(|key| LIST_FIELDS.contains(key)) {
+        return Value::Array(
+            text.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .collect(),
+        );
+    }
+    Value::from(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    const CONFIG: &str = r#"# Team config
+[settings]
+version = "1.0"
+
+# Local tools
+[mcp.servers.local]
+command = "local-mcp" # built from source
+"#;
+
+    #[test]
+    fn test_add_server_keeps_formatting() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, CONFIG).unwrap();
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        let mut server = Table::new();
+        server.insert("command", toml_edit::value("npx"));
+        editor.add_server("git.hub", server).unwrap();
+        assert!(editor.add_server("local", Table::new()).is_err());
+
+        let backup = editor.save().unwrap().unwrap();
+        assert_eq!(fs::read_to_string(backup).unwrap(), CONFIG);
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            format!("{CONFIG}\n[mcp.servers.\"git.hub\"]\ncommand = \"npx\"\n")
+        );
+    }
+
+    #[test]
+    fn test_set_enable_and_remove() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, CONFIG).unwrap();
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        let mut server = Table::new();
+        server.insert("url", toml_edit::value("https://example.com/mcp"));
+        editor.add_server("git.hub", server).unwrap();
+
+        assert_eq!(
+            editor.set("git.hub.targets", "cursor, codex").unwrap(),
+            ("git.hub".to_string(), "targets".to_string())
+        );
+        editor.set("local.command", "other-mcp").unwrap();
+        editor.set("local.env.TIMEOUT", "30").unwrap();
+        editor.set("local.startup_timeout_sec", "30").unwrap();
+        editor.set_enabled("local", false).unwrap();
+        assert!(editor.set("nope.command", "x").is_err());
+        assert!(editor.set("local", "x").is_err());
+        editor.save().unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.contains("command = \"other-mcp\" # built from source\n"));
+        assert!(content.contains("env = { TIMEOUT = \"30\" }\n"));
+        assert!(content.contains("startup_timeout_sec = 30\n"));
+        assert!(content.contains("enabled = false\n"));
+        assert!(content.contains("targets = [\"cursor\", \"codex\"]\n"));
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        editor.remove_server("git.hub").unwrap();
+        assert!(editor.remove_server("git.hub").is_err());
+        assert!(!editor.content().contains("git.hub"));
+    }
+
+    #[test]
+    fn test_save_rejects_invalid_config() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, CONFIG).unwrap();
+
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        let mut server = Table::new();
+        server.insert("command", toml_edit::value(""));
+        editor.add_server("empty", server).unwrap();
+
+        let err = editor.save().unwrap_err();
+        assert!(err.to_string().contains("command cannot be empty"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), CONFIG);
+    }
+
+    #[test]
+    fn test_save_allows_variable_urls() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        let config = format!(
+            "{CONFIG}\n[env]\nMCP_URL = \"https://example.com/mcp\"\n\n[mcp.servers.remote]\nurl = \
+             \"{{MCP_URL}}\"\n"
+        );
+        fs::write(&path, &config).unwrap();
+
+        // The unexpanded URL fails validation, but the edit didn't add it
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        editor.set_enabled("local", false).unwrap();
+        editor.set("remote.targets", "codex").unwrap();
+        editor.save().unwrap();
+        assert!(
+            fs::read_to_string(&path)
+                .unwrap()
+                .contains("url = \"{MCP_URL}\"")
+        );
+
+        // A new server with a broken URL is still rejected
+        let mut editor = ConfigEditor::open(&path).unwrap();
+        let mut server = Table::new();
+        server.insert("url", toml_edit::value("example.com/mcp"));
+        editor.add_server("broken", server).unwrap();
+        assert!(editor.save().unwrap_err().to_string().contains("broken"));
+    }
+}
@@ -3,10 +3,20 @@
 //! This module handles parsing, validation, and manipulation of the unified
 //! TOML configuration format.
 
+pub mod capabilities;
+pub mod codes;
+pub mod command;
+pub mod edit;
+pub mod package;
 pub mod parser;
 pub mod types;
 pub mod validator;
 
-pub use parser::{parse_and_expand_config, parse_config_file};
+pub use parser::{
+    parse_and_expand_config, parse_and_expand_config_with_secrets, parse_config_file,
+};
 pub use types::*;
-pub use validator::{ValidationError, validate_config};
+pub use validator::{
+    ValidationError, check_config, check_executables, check_packages, check_permissions,
+    check_working_dirs, filter_allowed, lint_credentials, validate_config,
+};
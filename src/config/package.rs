@@ -0,0 +1,523 @@
+//! Package shorthands for STDIO servers
+//!
+//! `package = "npm:@scope/name@1.4.2"` (or `pypi:` and `cargo:`) runs a
+//! published package through its package manager's runner instead of a
+//! command: `npx -y`, `uvx`, or `cargo install` followed by the installed
+//! binary. Versions missing from the config are taken from the lock file
+//! next to it, written by `multi-agent-config lock`, so every machine runs
+//! the same version.
+
+use crate::config::types::{MultiAgentConfig, ServerConfig};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// File name of the lock file, next to the unified config
+pub const LOCK_FILE_NAME: &str = "packages.lock";
+
+/// Script run by `sh -c` for cargo packages: `$0` is the crate, `$1` the
+/// version (empty for the latest), the rest the server's arguments
+pub const CARGO_WRAPPER: &str =
+    r#"cargo install --quiet --locked "$0" ${1:+--version "$1"} >&2 && shift && exec "$0" "$@""#;
+
+/// Package manager a package is published to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    /// npm, run with `npx`
+    Npm,
+    /// The Python Package Index, run with `uvx`
+    Pypi,
+    /// crates.io, installed with `cargo install`
+    Cargo,
+}
+
+impl PackageManager {
+    /// Prefix of the shorthand, e.g. `npm`
+    #[must_use]
+    pub const fn prefix(self) -> &'static str {
+        match self {
+            Self::Npm => "npm",
+            Self::Pypi => "pypi",
+            Self::Cargo => "cargo",
+        }
+    }
+
+    /// Executable that has to be installed to run the package
+    #[must_use]
+    pub const fn runner(self) -> &'static str {
+        match self {
+            Self::Npm => "npx",
+            Self::Pypi => "uvx",
+            Self::Cargo => "cargo",
+        }
+    }
+
+    /// Whether a version names exactly one release
+    ///
+    /// npm and cargo take a version requirement, so only a full semver
+    /// version (`1.4.2`, `2.0.0-rc.1`) is exact; `uvx` takes a single PEP 440
+    /// version (`0.6`, `1.0rc1`). Tags such as `latest` and ranges such as
+    /// `^1.4` or `>=0.6` are not.
+    #[must_use]
+    pub fn is_exact_version(self, version: &str) -> bool {
+        let allowed = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+' | '_');
+        if !version.starts_with(|c: char| c.is_ascii_digit())
+            || version.ends_with('.')
+            || !version
+                .chars()
+                .all(|c| allowed(c) || (self == Self::Pypi && c == '!'))
+        {
+            return false;
+        }
+        match self {
+            Self::Npm | Self::Cargo => {
+                let core = version.split(['-', '+']).next().unwrap_or_default();
+                let parts: Vec<&str> = core.split('.').collect();
+                parts.len() == 3
+                    && parts
+                        .iter()
+                        .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+            }
+            Self::Pypi => version.split('.').all(|part| !part.is_empty()),
+        }
+    }
+
+    /// Registry URL describing the package's latest release
+    fn registry_url(self, name: &str) -> String {
+        match self {
+            Self::Npm => format!(
+                "https://registry.npmjs.org/{}/latest",
+                name.replace('/', "%2F")
+            ),
+            Self::Pypi => format!("https://pypi.org/pypi/{name}/json"),
+            Self::Cargo => format!("https://crates.io/api/v1/crates/{name}"),
+        }
+    }
+}
+
+/// A parsed `package` shorthand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageSpec {
+    /// Registry the package comes from
+    pub manager: PackageManager,
+
+    /// Package name, e.g. `@modelcontextprotocol/server-github`
+    pub name: String,
+
+    /// Version pinned in the config, if any
+    pub version: Option<String>,
+}
+
+impl PackageSpec {
+    /// Parse `<manager>:<name>[@<version>]`
+    ///
+    /// # Errors
+    ///
+    /// Returns a message if the manager is unknown, the name or version is
+    /// empty or contains whitespace, or the version is a tag or range
+    /// rather than an exact version
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (prefix, package) = spec.split_once(':').ok_or_else(|| {
+            format!("Package '{spec}' must start with 'npm:', 'pypi:' or 'cargo:'")
+        })?;
+        let manager = match prefix {
+            "npm" => PackageManager::Npm,
+            "pypi" => PackageManager::Pypi,
+            "cargo" => PackageManager::Cargo,
+            _ => {
+                return Err(format!(
+                    "Package '{spec}' has unknown package manager '{prefix}' (expected npm, pypi \
+                     or cargo)"
+                ));
+            }
+        };
+
+        // npm scopes start with '@', so only a later '@' starts the version
+        let (name, version) = package
+            .rfind('@')
+            .filter(|&at| at > 0)
+            .map_or((package, None), |at| {
+                (&package[..at], Some(&package[at + 1..]))
+            });
+        let invalid = |part: &str| part.is_empty() || part.contains(char::is_whitespace);
+        if invalid(name) {
+            return Err(format!("Package '{spec}' has an empty or invalid name"));
+        }
+        if version.is_some_and(invalid) {
+            return Err(format!("Package '{spec}' has an empty or invalid version"));
+        }
+        if let Some(version) = version.filter(|v| !manager.is_exact_version(v)) {
+            return Err(format!(
+                "Package '{spec}' has version '{version}', which is not an exact version; pin \
+                 one (e.g. '@1.4.2') or leave it out and run 'multi-agent-config lock'"
+            ));
+        }
+
+        Ok(Self {
+            manager,
+            name: name.to_string(),
+            version: version.map(str::to_string),
+        })
+    }
+
+    /// Lock file key: the shorthand without its version
+    #[must_use]
+    pub fn key(&self) -> String {
+        format!("{}:{}", self.manager.prefix(), self.name)
+    }
+
+    /// Command and leading arguments that run the package
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - Version to run, `None` for the latest
+    #[must_use]
+    pub fn command(&self, version: Option<&str>) -> (String, Vec<String>) {
+        let pinned = || {
+            version.map_or_else(
+                || self.name.clone(),
+                |version| format!("{}@{version}", self.name),
+            )
+        };
+        match self.manager {
+            PackageManager::Npm => ("npx".to_string(), vec!["-y".to_string(), pinned()]),
+            PackageManager::Pypi => ("uvx".to_string(), vec![pinned()]),
+            PackageManager::Cargo => (
+                "sh".to_string(),
+                vec![
+                    "-c".to_string(),
+                    CARGO_WRAPPER.to_string(),
+                    self.name.clone(),
+                    version.unwrap_or_default().to_string(),
+                ],
+            ),
+        }
+    }
+}
+
+impl fmt::Display for PackageSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.key())?;
+        if let Some(version) = &self.version {
+            write!(f, "@{version}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Versions resolved by `multi-agent-config lock`, keyed by
+/// [`PackageSpec::key`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Version of each package
+    #[serde(default)]
+    pub packages: BTreeMap<String, String>,
+}
+
+impl Lockfile {
+    /// Read a lock file; a missing file is an empty lock
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed
+    pub fn load(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content =
+            fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {e}", path.display()))?;
+        toml::from_str(&content).map_err(|e| format!("Cannot parse {}: {e}", path.display()))
+    }
+
+    /// Lock file content
+    #[must_use]
+    pub fn render(&self) -> String {
+        let body = toml::to_string(self).unwrap_or_default();
+        format!("# Generated by multi-agent-config lock; do not edit\n\n{body}")
+    }
+
+    /// Version a package runs: the config's, else the locked one
+    #[must_use]
+    pub fn version<'a>(&'a self, spec: &'a PackageSpec) -> Option<&'a str> {
+        spec.version
+            .as_deref()
+            .or_else(|| self.packages.get(&spec.key()).map(String::as_str))
+    }
+}
+
+/// Path of the lock file for a config file
+#[must_use]
+pub fn lock_file_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(LOCK_FILE_NAME)
+}
+
+/// Servers with a valid `package`, sorted by name
+#[must_use]
+pub fn package_servers(config: &MultiAgentConfig) -> Vec<(&str, PackageSpec)> {
+    let sorted: BTreeMap<_, _> = config.mcp.servers.iter().collect();
+    sorted
+        .into_iter()
+        .filter_map(|(name, server)| match server {
+            ServerConfig::Stdio(stdio) => {
+                let spec = PackageSpec::parse(stdio.package.as_deref()?).ok()?;
+                Some((name.as_str(), spec))
+            }
+            ServerConfig::Http(_) => None,
+        })
+        .collect()
+}
+
+/// Rewrite servers with a `package` to the command that runs it
+///
+/// The package's arguments come before the server's `args`, and before the
+/// `args` of each of its overrides. Servers with an invalid `package` are
+/// left alone; validation reports them.
+///
+/// # Arguments
+///
+/// * `config` - Expanded configuration, rewritten in place
+/// * `lock` - Versions for packages the config does not pin
+///
+/// # Returns
+///
+/// A warning for each package that is neither pinned nor locked
+pub fn pin_packages(config: &mut MultiAgentConfig, lock: &Lockfile) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let sorted: BTreeMap<_, _> = config.mcp.servers.iter_mut().collect();
+    for (name, server) in sorted {
+        let ServerConfig::Stdio(stdio) = server else {
+            continue;
+        };
+        let Some(Ok(spec)) = stdio.package.as_deref().map(PackageSpec::parse) else {
+            continue;
+        };
+
+        let version = lock.version(&spec);
+        if version.is_none() {
+            warnings.push(format!(
+                "Server '{name}': package '{spec}' is not pinned or locked; emitting the latest \
+                 version (run 'multi-agent-config lock')"
+            ));
+        }
+        let (command, prefix) = spec.command(version);
+        stdio.command = command;
+        stdio.args.splice(0..0, prefix.iter().cloned());
+        for over in stdio.overrides.values_mut() {
+            if let Some(args) = &mut over.args {
+                args.splice(0..0, prefix.iter().cloned());
+            }
+        }
+    }
+    warnings
+}
+
+/// Latest version of a package, from its registry
+///
+/// Fetched with `curl`, so it works without the package manager installed.
+///
+/// # Errors
+///
+/// Returns a message if `curl` fails or the response has no version
+pub fn latest_version(spec: &PackageSpec) -> Result<String, String> {
+    let url = spec.manager.registry_url(&spec.name);
+    let output = Command::new("curl")
+        .args(["-fsSL", "-A", "multi-agent-config", &url])
+        .output()
+        .map_err(|e| format!("Cannot run curl for '{}': {e}", spec.key()))?;
+    if !output.status.success() {
+        return Err(format!(
+            "Cannot fetch '{}' from {url}: {}",
+            spec.key(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    parse_latest(spec.manager, &String::from_utf8_lossy(&output.stdout))
+        .ok_or_else(|| format!("No version for '{}' in the response from {url}", spec.key()))
+}
+
+/// Latest version in a registry response
+fn parse_latest(manager: PackageManager, body: &str) -> Option<String> {
+    let json: serde_json::Value = serde_json::from_str(body).ok()?;
+    let version = match manager {
+        PackageManager::Npm => &json["version"],
+        PackageManager::Pypi => &json["info"]["version"],
+        PackageManager::Cargo => {
+            let info = &json["crate"];
+            if info["max_stable_version"].is_string() {
+                &info["max_stable_version"]
+            } else {
+                &info["max_version"]
+            }
+        }
+    };
+    version.as_str().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_package_spec() {
+        let spec = PackageSpec::parse("npm:@modelcontextprotocol/server-github@1.4.2").unwrap();
+        assert_eq!(spec.manager, PackageManager::Npm);
+        assert_eq!(spec.name, "@modelcontextprotocol/server-github");
+        assert_eq!(spec.version.as_deref(), Some("1.4.2"));
+        assert_eq!(spec.key(), "npm:@modelcontextprotocol/server-github");
+        assert_eq!(
+            spec.to_string(),
+            "npm:@modelcontextprotocol/server-github@1.4.2"
+        );
+
+        let spec = PackageSpec::parse("pypi:mcp-server-git").unwrap();
+        assert_eq!(spec.manager, PackageManager::Pypi);
+        assert_eq!(spec.version, None);
+
+        assert!(PackageSpec::parse("server-github").is_err());
+        assert!(PackageSpec::parse("gem:rails").is_err());
+        assert!(PackageSpec::parse("npm:").is_err());
+        assert!(PackageSpec::parse("cargo:tool@").is_err());
+    }
+
+    #[test]
+    fn test_parse_package_rejects_tags_and_ranges() {
+        let latest = PackageSpec::parse("npm:@scope/server@latest").unwrap_err();
+        assert!(latest.contains("version 'latest', which is not an exact version"));
+        assert!(PackageSpec::parse("pypi:mcp-server-git@>=0.6").is_err());
+        assert!(PackageSpec::parse("npm:server@^1.4.2").is_err());
+        assert!(PackageSpec::parse("npm:server@1.4").is_err());
+        assert!(PackageSpec::parse("npm:server@1.x.0").is_err());
+        assert!(PackageSpec::parse("cargo:tool@~0.3").is_err());
+        assert!(PackageSpec::parse("pypi:tool@0.6.*").is_err());
+
+        assert!(PackageSpec::parse("npm:server@2.0.0-rc.1").is_ok());
+        assert!(PackageSpec::parse("cargo:tool@0.3.1+build.5").is_ok());
+        assert!(PackageSpec::parse("pypi:tool@0.6").is_ok());
+        assert!(PackageSpec::parse("pypi:tool@1.0rc1").is_ok());
+    }
+
+    #[test]
+    fn test_package_command() {
+        let npm = PackageSpec::parse("npm:@scope/server").unwrap();
+        assert_eq!(
+            npm.command(Some("1.0.0")),
+            (
+                "npx".to_string(),
+                vec!["-y".to_string(), "@scope/server@1.0.0".to_string()]
+            )
+        );
+        assert_eq!(npm.command(None).1, ["-y", "@scope/server"]);
+
+        let pypi = PackageSpec::parse("pypi:mcp-server-git").unwrap();
+        assert_eq!(pypi.command(Some("0.6.2")).1, ["mcp-server-git@0.6.2"]);
+
+        let cargo = PackageSpec::parse("cargo:mcp-tool").unwrap();
+        assert_eq!(
+            cargo.command(Some("0.3.1")),
+            (
+                "sh".to_string(),
+                vec![
+                    "-c".to_string(),
+                    CARGO_WRAPPER.to_string(),
+                    "mcp-tool".to_string(),
+                    "0.3.1".to_string(),
+                ]
+            )
+        );
+    }
+
+    #[test]
+    fn test_pin_packages() {
+        let mut config: MultiAgentConfig = toml::from_str(
+            r#"
+[settings]
+version = "1.0"
+
+[mcp.servers.github]
+package = "npm:@modelcontextprotocol/server-github"
+args = ["--stdio"]
+
+[mcp.servers.github.overrides.codex]
+args = ["--read-only"]
+
+[mcp.servers.git]
+package = "pypi:mcp-server-git@0.6.2"
+
+[mcp.servers.fetch]
+package = "pypi:mcp-server-fetch"
+"#,
+        )
+        .unwrap();
+        let lock = Lockfile {
+            packages: BTreeMap::from([(
+                "npm:@modelcontextprotocol/server-github".to_string(),
+                "1.4.2".to_string(),
+            )]),
+        };
+
+        let warnings = pin_packages(&mut config, &lock);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("Server 'fetch': package 'pypi:mcp-server-fetch'"));
+
+        let ServerConfig::Stdio(github) = &config.mcp.servers["github"] else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(github.command, "npx");
+        assert_eq!(
+            github.args,
+            ["-y", "@modelcontextprotocol/server-github@1.4.2", "--stdio"]
+        );
+        assert_eq!(
+            github.overrides["codex"].args.as_deref().unwrap(),
+            [
+                "-y",
+                "@modelcontextprotocol/server-github@1.4.2",
+                "--read-only"
+            ]
+        );
+
+        let ServerConfig::Stdio(git) = &config.mcp.servers["git"] else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(git.args, ["mcp-server-git@0.6.2"]);
+    }
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let lock = Lockfile {
+            packages: BTreeMap::from([("pypi:mcp-server-git".to_string(), "0.6.2".to_string())]),
+        };
+        let content = lock.render();
+        assert!(content.starts_with("# Generated by multi-agent-config lock"));
+        assert_eq!(toml::from_str::<Lockfile>(&content).unwrap(), lock);
+        assert_eq!(
+            lock_file_path(Path::new("/home/me/.config/multi-agent-config/config.toml")),
+            PathBuf::from("/home/me/.config/multi-agent-config/packages.lock")
+        );
+    }
+
+    #[test]
+    fn test_parse_latest() {
+        assert_eq!(
+            parse_latest(PackageManager::Npm, r#"{"name":"x","version":"1.4.2"}"#).as_deref(),
+            Some("1.4.2")
+        );
+        assert_eq!(
+            parse_latest(PackageManager::Pypi, r#"{"info":{"version":"0.6.2"}}"#).as_deref(),
+            Some("0.6.2")
+        );
+        assert_eq!(
+            parse_latest(
+                PackageManager::Cargo,
+                r#"{"crate":{"max_version":"0.4.0-rc.1","max_stable_version":"0.3.1"}}"#
+            )
+            .as_deref(),
+            Some("0.3.1")
+        );
+        assert_eq!(parse_latest(PackageManager::Npm, "not json"), None);
+    }
+}
@@ -1,9 +1,17 @@
 //! TOML configuration file parsing
 
 use crate::{
-    config::types::{MultiAgentConfig, ServerConfig},
+    config::{
+        command::expand_tilde,
+        types::{
+            ContainerConfig, ExpandMode, HttpAuth, MultiAgentConfig, ServerConfig, ServerOverride,
+            StdioServerConfig,
+        },
+    },
+    diagnostics::SourceFile,
     error::{ConfigError, MultiAgentError},
-    expand::Expander,
+    expand::{Expander, SecretRegistry, is_secret_name},
+    secrets::{SecretsFile, secrets_file_path, store::key_file_path},
 };
 use std::{collections::HashMap, env, fs, path::Path};
 
@@ -22,21 +30,26 @@ use std::{collections::HashMap, env, fs, path::Path};
 ///
 /// * `ConfigError::FileNotFound` - File does not exist
 /// * `ConfigError::PermissionDenied` - Cannot read file due to permissions
-/// * `ConfigError::ParseError` - TOML syntax error
+/// * `ConfigError::ParseError` - TOML syntax error, or TOML that doesn't
+///   match the schema
 /// * `ConfigError::IoError` - Other I/O error
 pub fn parse_config_file(path: &Path) -> Result<MultiAgentConfig, ConfigError> {
     // Read the file contents
     let contents = read_file_utf8(path)?;
 
-    // Parse TOML
+    // Parse TOML; errors in well-formed TOML are schema errors
     let config: MultiAgentConfig = toml::from_str(&contents).map_err(|e| {
-        // Extract line number from toml error if available
-        let line = e.span().map_or(0, |span| {
-            // Count newlines up to the error position
-            contents[..span.start].lines().count()
-        });
-
-        ConfigError::parse_error(e.message(), line)
+        e.span().map_or_else(
+            || ConfigError::parse_error(e.message(), 0),
+            |span| {
+                let source = SourceFile::new(path, contents.as_str());
+                if contents.parse::<toml::Table>().is_ok() {
+                    ConfigError::schema_error_at(e.message(), &source, span)
+                } else {
+                    ConfigError::parse_error_at(e.message(), &source, span)
+                }
+            },
+        )
     })?;
 
     Ok(config)
@@ -88,6 +101,47 @@ pub fn read_file_utf8(path: &Path) -> Result<String, ConfigError> {
 /// Returns error if file cannot be read, TOML is invalid, or variable expansion
 /// fails
 pub fn parse_and_expand_config(path: &Path) -> Result<MultiAgentConfig, MultiAgentError> {
+    parse_and_expand_config_with_secrets(path).map(|(config, _)| config)
+}
+
+/// Parse and expand configuration, tracking secret values
+///
+/// Like [`parse_and_expand_config`], but also returns the secret values it
+/// references: shell variables that flowed into secret-bearing fields
+/// (server `env` values, `bearer_token` and `auth`), whether substituted or
+/// deferred to runtime, variables whose names look like credentials, the
+/// expanded values of credential-named `env` keys and headers, bearer tokens
+/// and `auth` credentials, and everything in the encrypted secrets file.
+///
+/// Values from `secrets.toml.enc` next to the config are merged into `[env]`;
+/// `[env]` wins when both define a name.
+///
+/// # Arguments
+///
+/// * `path` - Path to the TOML configuration file
+///
+/// # Returns
+///
+/// * `Ok((MultiAgentConfig, SecretRegistry))` - Expanded configuration and the
+///   secrets it references
+/// * `Err(MultiAgentError)` - Error reading, parsing, or expanding the file
+///
+/// # Errors
+///
+/// Returns error if file cannot be read, TOML is invalid, the secrets file
+/// cannot be decrypted, or variable expansion fails
+pub fn parse_and_expand_config_with_secrets(
+    path: &Path,
+) -> Result<(MultiAgentConfig, SecretRegistry), MultiAgentError> {
+    parse_and_expand_with_key_file(path, &key_file_path())
+}
+
+/// [`parse_and_expand_config_with_secrets`], decrypting the secrets file
+/// with a given key file
+fn parse_and_expand_with_key_file(
+    path: &Path,
+    key_file: &Path,
+) -> Result<(MultiAgentConfig, SecretRegistry), MultiAgentError> {
     // Parse the configuration
     let mut config = parse_config_file(path)?;
 
@@ -95,40 +149,84 @@ pub fn parse_and_expand_config(path: &Path) -> Result<MultiAgentConfig, MultiAge
     let shell_env: HashMap<String, String> = env::vars().collect();
 
     // Get [env] section
-    let env_section = config.env.clone().unwrap_or_default();
+    let mut env_section = config.env.clone().unwrap_or_default();
+    let mut secrets = SecretRegistry::new();
+
+    // Merge values from the encrypted secrets file, if there is one
+    let secrets_path = secrets_file_path(path);
+    if secrets_path.exists() {
+        let secrets_file = SecretsFile::load_with_key_file(&secrets_path, key_file)?;
+        for (name, value) in secrets_file.values() {
+            secrets.record(name.clone(), value.clone());
+            if env_section.contains_key(name) {
+                eprintln!(
+                    "Warning: [env] defines '{name}', which is also in {}; using [env]",
+                    secrets_path.display()
+                );
+            } else {
+                env_section.insert(name.clone(), value.clone());
+            }
+        }
+    }
 
     // Create expander
     let mut expander = Expander::new(env_section, shell_env);
 
     // Expand variables in all server configurations
-    for server in config.mcp.servers.values_mut() {
+    for (server_name, server) in &mut config.mcp.servers {
+        let mode = server.expand_mode(config.settings.as_ref());
+        expander.set_defer_shell_vars(mode == ExpandMode::Runtime);
+
         match server {
             ServerConfig::Stdio(stdio) => {
-                // Expand command
-                stdio.command = expander.expand(&stdio.command)?;
-
-                // Expand args
-                for arg in &mut stdio.args {
-                    *arg = expander.expand(arg)?;
-                }
-
-                // Expand env vars if present
-                if let Some(server_env) = &mut stdio.env {
-                    for (_key, value) in server_env.iter_mut() {
-                        *value = expander.expand(value)?;
-                    }
-                }
+                expand_stdio_server(&mut expander, &mut secrets, server_name, stdio)?;
             }
             ServerConfig::Http(http) => {
                 // Expand URL
                 http.url = expander.expand(&http.url)?;
+                collect_secrets(&mut expander, &mut secrets, false);
 
                 // Expand bearer_token if present
                 if let Some(token) = &mut http.bearer_token {
                     *token = expander.expand(token)?;
+                    collect_secrets(&mut expander, &mut secrets, true);
+                    record_value(&mut secrets, &format!("{server_name}.bearer_token"), token);
+                }
+
+                // Expand headers; credential-named headers are secrets
+                if let Some(headers) = &mut http.headers {
+                    for (name, value) in headers.iter_mut() {
+                        *value = expander.expand(value)?;
+                        let secret_header = is_secret_name(name);
+                        collect_secrets(&mut expander, &mut secrets, secret_header);
+                        if secret_header {
+                            let field = format!("{server_name}.headers.{name}");
+                            record_value(&mut secrets, &field, value);
+                        }
+                    }
+                }
+
+                // Expand auth credentials
+                if let Some(auth) = &mut http.auth {
+                    let credential = match auth {
+                        HttpAuth::Bearer { token } => token,
+                        HttpAuth::Basic { username, password } => {
+                            *username = expander.expand(username)?;
+                            password
+                        }
+                        HttpAuth::Header { value, .. } => value,
+                    };
+                    *credential = expander.expand(credential)?;
+                    collect_secrets(&mut expander, &mut secrets, true);
+                    record_value(&mut secrets, &format!("{server_name}.auth"), credential);
                 }
             }
         }
+
+        for (tool, over) in server.overrides_mut() {
+            let ctx = format!("{server_name}.overrides.{tool}");
+            expand_override(&mut expander, &mut secrets, &ctx, over)?;
+        }
     }
 
     // Log warnings if any
@@ -136,7 +234,155 @@ pub fn parse_and_expand_config(path: &Path) -> Result<MultiAgentConfig, MultiAge
         eprintln!("Warning: {warning}");
     }
 
-    Ok(config)
+    Ok((config, secrets))
+}
+
+/// Expand a STDIO server's command, args, working directory and env
+fn expand_stdio_server(
+    expander: &mut Expander,
+    secrets: &mut SecretRegistry,
+    server_name: &str,
+    stdio: &mut StdioServerConfig,
+) -> Result<(), MultiAgentError> {
+    // Expand command
+    stdio.command = expander.expand(&stdio.command)?;
+    if let Some(package) = &mut stdio.package {
+        *package = expander.expand(package)?;
+    }
+
+    // Expand args
+    for arg in &mut stdio.args {
+        *arg = expander.expand(arg)?;
+    }
+    collect_secrets(expander, secrets, false);
+
+    // Expand the working directory, then a leading `~`
+    if let Some(cwd) = &mut stdio.cwd {
+        let dir = expander.expand(cwd)?;
+        *cwd = expand_tilde(&dir).to_string_lossy().into_owned();
+        collect_secrets(expander, secrets, false);
+    }
+
+    // Expand env vars if present
+    if let Some(server_env) = &mut stdio.env {
+        for (key, value) in server_env.iter_mut() {
+            *value = expander.expand(value)?;
+            collect_secrets(expander, secrets, true);
+            if is_secret_name(key) {
+                record_value(secrets, &format!("{server_name}.env.{key}"), value);
+            }
+        }
+    }
+
+    if let Some(container) = &mut stdio.container {
+        expand_container(expander, secrets, server_name, container)?;
+    }
+
+    Ok(())
+}
+
+/// Expand a container's image, network, mounts and env
+///
+/// A leading `~` in a mount's host path is expanded like `cwd`.
+fn expand_container(
+    expander: &mut Expander,
+    secrets: &mut SecretRegistry,
+    server_name: &str,
+    container: &mut ContainerConfig,
+) -> Result<(), MultiAgentError> {
+    container.image = expander.expand(&container.image)?;
+    if let Some(network) = &mut container.network {
+        *network = expander.expand(network)?;
+    }
+    for mount in &mut container.mounts {
+        let value = expander.expand(mount)?;
+        *mount = match value.split_once(':') {
+            Some((host, rest)) => {
+                format!("{}:{rest}", expand_tilde(host).to_string_lossy())
+            }
+            None => value,
+        };
+    }
+    collect_secrets(expander, secrets, false);
+
+    for (key, value) in &mut container.env {
+        *value = expander.expand(value)?;
+        collect_secrets(expander, secrets, true);
+        if is_secret_name(key) {
+            record_value(
+                secrets,
+                &format!("{server_name}.container.env.{key}"),
+                value,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Expand the values of one tool's overrides like the fields they replace
+fn expand_override(
+    expander: &mut Expander,
+    secrets: &mut SecretRegistry,
+    ctx: &str,
+    over: &mut ServerOverride,
+) -> Result<(), MultiAgentError> {
+    for value in over
+        .command
+        .iter_mut()
+        .chain(over.args.iter_mut().flatten())
+        .chain(over.url.iter_mut())
+    {
+        *value = expander.expand(value)?;
+        collect_secrets(expander, secrets, false);
+    }
+
+    if let Some(env) = &mut over.env {
+        for (key, value) in env.iter_mut() {
+            *value = expander.expand(value)?;
+            collect_secrets(expander, secrets, true);
+            if is_secret_name(key) {
+                record_value(secrets, &format!("{ctx}.env.{key}"), value);
+            }
+        }
+    }
+
+    if let Some(headers) = &mut over.headers {
+        for (name, value) in headers.iter_mut() {
+            *value = expander.expand(value)?;
+            let secret_header = is_secret_name(name);
+            collect_secrets(expander, secrets, secret_header);
+            if secret_header {
+                record_value(secrets, &format!("{ctx}.headers.{name}"), value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Record the variables referenced since the last call that hold secrets
+///
+/// Shell variables in secret-bearing fields are always secrets; otherwise a
+/// variable is a secret when its name looks like a credential.
+fn collect_secrets(expander: &mut Expander, secrets: &mut SecretRegistry, secret_field: bool) {
+    for (name, value) in expander.take_shell_refs() {
+        if secret_field || is_secret_name(&name) {
+            secrets.record(name, value);
+        }
+    }
+    for (name, value) in expander.take_config_refs() {
+        if is_secret_name(&name) {
+            record_value(secrets, &name, &value);
+        }
+    }
+}
+
+/// Record an expanded value unless it is still a runtime reference
+fn record_value(secrets: &mut SecretRegistry, name: &str, value: &str) {
+    if !value.contains("${") {
+        secrets.record(name, value);
+    }
 }
 
 #[cfg(test)]
@@ -247,9 +493,15 @@ version = "1.0"  # Missing closing bracket
 
         assert!(result.is_err());
         match result {
-            Err(ConfigError::ParseError { message, line }) => {
+            Err(ConfigError::ParseError {
+                message,
+                line,
+                syntax,
+                ..
+            }) => {
                 assert!(!message.is_empty());
                 assert!(line > 0);
+                assert!(syntax);
             }
             Err(e) => panic!("Expected ParseError, got: {e}"),
             Ok(_) => panic!("Expected error, got success"),
@@ -274,6 +526,38 @@ version = "1.0"
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_server_error_line() {
+        let toml_content = r#"[settings]
+version = "1.0"
+
+[mcp.servers.ok]
+command = "npx"
+
+[mcp.servers.both]
+command = "npx"
+url = "https://example.com/mcp"
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(toml_content.as_bytes()).unwrap();
+
+        match parse_config_file(temp_file.path()) {
+            Err(ConfigError::ParseError {
+                message,
+                line,
+                syntax,
+                ..
+            }) => {
+                assert!(message.starts_with("Server 'both' has both"));
+                assert_eq!(line, 7);
+                // Well-formed TOML, so not a syntax error
+                assert!(!syntax);
+            }
+            other => panic!("Expected ParseError, got: {other:?}"),
+        }
+    }
+
     #[test]
     fn test_read_file_utf8_success() {
         let content = "test content";
@@ -373,4 +657,178 @@ command = "{A}"
         let result = parse_and_expand_config(temp_file.path());
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_and_expand_runtime_mode() {
+        env::set_var("TEST_RUNTIME_TOKEN", "runtime_secret");
+
+        let toml_content = r#"
+[settings]
+version = "1.0"
+
+[env]
+TOKEN = "${TEST_RUNTIME_TOKEN}"
+
+[mcp.servers.deferred]
+command = "npx"
+expand = "runtime"
+
+[mcp.servers.deferred.env]
+API_KEY = "{TOKEN}"
+
+[mcp.servers.inlined]
+command = "npx"
+
+[mcp.servers.inlined.env]
+API_KEY = "{TOKEN}"
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(toml_content.as_bytes()).unwrap();
+
+        let (config, secrets) = parse_and_expand_config_with_secrets(temp_file.path()).unwrap();
+
+        let Some(ServerConfig::Stdio(deferred)) = config.mcp.servers.get("deferred") else {
+            panic!("deferred server not found or wrong type");
+        };
+        assert_eq!(
+            deferred.env.as_ref().unwrap().get("API_KEY").unwrap(),
+            "${TEST_RUNTIME_TOKEN}"
+        );
+
+        let Some(ServerConfig::Stdio(inlined)) = config.mcp.servers.get("inlined") else {
+            panic!("inlined server not found or wrong type");
+        };
+        assert_eq!(
+            inlined.env.as_ref().unwrap().get("API_KEY").unwrap(),
+            "runtime_secret"
+        );
+
+        assert_eq!(
+            secrets.names_found_in("runtime_secret"),
+            vec!["TEST_RUNTIME_TOKEN"]
+        );
+
+        env::remove_var("TEST_RUNTIME_TOKEN");
+    }
+
+    #[test]
+    fn test_secrets_tracked_by_name() {
+        let toml_content = r#"
+[env]
+SERVICE_API_KEY = "sk_from_config"
+LOG_LEVEL = "debug-verbose"
+
+[mcp.servers.args]
+command = "server"
+args = ["--key={SERVICE_API_KEY}", "--log={LOG_LEVEL}"]
+
+[mcp.servers.args.env]
+DB_PASSWORD = "hunter22"
+
+[mcp.servers.remote]
+url = "https://example.com/mcp"
+bearer_token = "literal_bearer"
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(toml_content.as_bytes()).unwrap();
+
+        let (_, secrets) = parse_and_expand_config_with_secrets(temp_file.path()).unwrap();
+
+        assert_eq!(secrets.len(), 3);
+        assert_eq!(
+            secrets.names_found_in("sk_from_config hunter22 literal_bearer"),
+            vec![
+                "SERVICE_API_KEY",
+                "args.env.DB_PASSWORD",
+                "remote.bearer_token"
+            ]
+        );
+        assert!(secrets.names_found_in("debug-verbose").is_empty());
+    }
+
+    #[test]
+    fn test_secrets_tracked_in_headers_and_auth() {
+        let toml_content = r#"
+[env]
+TEAM = "platform"
+
+[mcp.servers.remote]
+url = "https://example.com/mcp"
+auth = { type = "header", name = "X-Auth", value = "literal_auth" }
+
+[mcp.servers.remote.headers]
+X-Team = "{TEAM}"
+X-API-Key = "literal_key"
+"#;
+
+        let mut temp_file = NamedTempFile::new().unwrap();
+        temp_file.write_all(toml_content.as_bytes()).unwrap();
+
+        let (config, secrets) = parse_and_expand_config_with_secrets(temp_file.path()).unwrap();
+
+        if let Some(ServerConfig::Http(http)) = config.mcp.servers.get("remote") {
+            assert_eq!(http.headers.as_ref().unwrap()["X-Team"], "platform");
+        } else {
+            panic!("Expected HTTP server");
+        }
+        assert_eq!(
+            secrets.names_found_in("platform literal_auth literal_key"),
+            vec!["remote.auth", "remote.headers.X-API-Key"]
+        );
+    }
+
+    #[test]
+    fn test_secrets_file_feeds_env() {
+        use crate::secrets::store::generate_key_file;
+        use tempfile::TempDir;
+
+        // The key file is passed in rather than set through
+        // MULTI_AGENT_CONFIG_KEY_FILE, which other tests would see
+        let temp_dir = TempDir::new().unwrap();
+        let key_path = temp_dir.path().join("secrets.key");
+        generate_key_file(&key_path, false).unwrap();
+
+        let config_path = temp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            r#"
+[env]
+SHADOWED = "from-env"
+
+[mcp.servers.test]
+command = "npx"
+
+[mcp.servers.test.env]
+TOKEN = "{TEAM_TOKEN}"
+OTHER = "{SHADOWED}"
+"#,
+        )
+        .unwrap();
+
+        let mut secrets_file =
+            SecretsFile::load_or_new_with_key_file(&secrets_file_path(&config_path), &key_path)
+                .unwrap();
+        secrets_file.set("TEAM_TOKEN", "team_secret_value");
+        secrets_file.set("SHADOWED", "from-secrets");
+        secrets_file.save().unwrap();
+
+        let (config, secrets) = parse_and_expand_with_key_file(&config_path, &key_path).unwrap();
+        let Some(ServerConfig::Stdio(stdio)) = config.mcp.servers.get("test") else {
+            panic!("test server not found or wrong type");
+        };
+        let server_env = stdio.env.as_ref().unwrap();
+        assert_eq!(server_env.get("TOKEN").unwrap(), "team_secret_value");
+        assert_eq!(server_env.get("OTHER").unwrap(), "from-env");
+        assert_eq!(
+            secrets.names_found_in("team_secret_value"),
+            vec!["TEAM_TOKEN"]
+        );
+
+        // Without the key the error says how to provide it
+        fs::remove_file(&key_path).unwrap();
+        let err = parse_and_expand_with_key_file(&config_path, &key_path).unwrap_err();
+        assert!(err.to_string().contains("secrets keygen"));
+    }
 }
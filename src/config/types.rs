@@ -3,8 +3,15 @@
 //! This module defines the types that represent the unified configuration
 //! schema.
 
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::config::capabilities::capabilities;
+use serde::{
+    Deserialize, Deserializer, Serialize,
+    de::{DeserializeSeed, Error as _, MapAccess, Visitor},
+};
+use std::collections::{BTreeMap, HashMap};
+
+/// Keys found in a table that are not part of the schema
+pub type UnknownFields = BTreeMap<String, toml::Value>;
 
 /// Root configuration structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +26,10 @@ pub struct MultiAgentConfig {
 
     /// Required MCP servers configuration
     pub mcp: McpConfig,
+
+    /// Keys not in the schema, reported by validation
+    #[serde(flatten, skip_serializing)]
+    pub unknown_fields: UnknownFields,
 }
 
 /// Settings section
@@ -30,6 +41,114 @@ pub struct Settings {
     /// Default target tools for servers
     #[serde(default = "default_targets")]
     pub default_targets: Vec<String>,
+
+    /// Default expansion mode for servers that don't set `expand`
+    #[serde(default)]
+    pub expand: ExpandMode,
+
+    /// What to do when a tool cannot express a runtime variable reference
+    #[serde(default)]
+    pub runtime_fallback: RuntimeFallback,
+
+    /// How to report literal values that look like credentials
+    #[serde(default)]
+    pub credential_lint: LintLevel,
+
+    /// How to report keys that are not part of the schema
+    #[serde(default = "default_unknown_keys")]
+    pub unknown_keys: LintLevel,
+
+    /// `PATH` used to resolve server commands, instead of the current one
+    #[serde(default)]
+    pub search_path: Option<String>,
+
+    /// Emit STDIO commands as the absolute path they resolve to at compile
+    /// time, for servers that don't set `resolve_command`
+    #[serde(default)]
+    pub resolve_command: bool,
+
+    /// Also set `PATH` to the search path on servers with a resolved command
+    #[serde(default)]
+    pub inject_path: bool,
+
+    /// What to do with `cwd` for tools that have no working directory field
+    #[serde(default)]
+    pub cwd_fallback: CwdFallback,
+
+    /// Start STDIO servers through generated launcher scripts, for servers
+    /// that don't set `launcher`
+    #[serde(default)]
+    pub launcher: bool,
+
+    /// Keys not in the schema, reported by validation
+    #[serde(flatten, skip_serializing)]
+    pub unknown_fields: UnknownFields,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            version: "1.0".to_string(),
+            default_targets: default_targets(),
+            expand: ExpandMode::default(),
+            runtime_fallback: RuntimeFallback::default(),
+            credential_lint: LintLevel::default(),
+            unknown_keys: default_unknown_keys(),
+            search_path: None,
+            resolve_command: false,
+            inject_path: false,
+            cwd_fallback: CwdFallback::default(),
+            launcher: false,
+            unknown_fields: UnknownFields::new(),
+        }
+    }
+}
+
+/// When shell variable references (`${VAR}`) are resolved
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExpandMode {
+    /// Substitute values at compile time (secrets end up in generated files)
+    #[default]
+    Compile,
+    /// Emit each tool's native runtime reference so the tool resolves the
+    /// variable when it launches the server
+    Runtime,
+}
+
+/// Behaviour for runtime references a tool has no syntax for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuntimeFallback {
+    /// Substitute the current value, as in compile-time expansion
+    #[default]
+    Inline,
+    /// Fail the transformation
+    Error,
+}
+
+/// Behaviour for `cwd` on tools with no working directory field
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CwdFallback {
+    /// Leave it out; validation warns that the tool ignores it
+    #[default]
+    Ignore,
+    /// Run the command through `sh -c` after changing directory
+    Wrap,
+}
+
+/// How seriously a lint finding is treated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LintLevel {
+    /// Skip the lint
+    Off,
+    /// Report findings as warnings
+    #[default]
+    Warn,
+    /// Report findings as validation errors
+    Error,
 }
 
 /// Default targets: cursor, opencode, codex
@@ -45,11 +164,16 @@ fn default_targets() -> Vec<String> {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpConfig {
     /// Map of server name to server configuration
+    #[serde(deserialize_with = "deserialize_servers")]
     pub servers: HashMap<String, ServerConfig>,
 }
 
 /// Server configuration (STDIO or HTTP)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Deserialized by the fields present: `command`, `package` or `container`
+/// makes a STDIO server and `url` a remote one. A server with more than one,
+/// neither, or fields that belong to the other kind is rejected.
+#[derive(Debug, Clone, Serialize)]
 #[serde(untagged)]
 pub enum ServerConfig {
     /// STDIO server configuration
@@ -58,16 +182,208 @@ pub enum ServerConfig {
     Http(HttpServerConfig),
 }
 
+/// Top-level sections of the config file
+pub const CONFIG_FIELDS: &[&str] = &["settings", "env", "mcp"];
+
+/// Fields accepted in `[settings]`
+pub const SETTINGS_FIELDS: &[&str] = &[
+    "version",
+    "default_targets",
+    "expand",
+    "runtime_fallback",
+    "credential_lint",
+    "unknown_keys",
+    "search_path",
+    "resolve_command",
+    "inject_path",
+    "cwd_fallback",
+    "launcher",
+];
+
+/// Fields accepted on a STDIO server
+pub const STDIO_FIELDS: &[&str] = &[
+    "command",
+    "package",
+    "container",
+    "args",
+    "cwd",
+    "enabled",
+    "targets",
+    "env",
+    "disabled",
+    "autoApprove",
+    "startup_timeout_sec",
+    "tool_timeout_sec",
+    "tools",
+    "expand",
+    "transport",
+    "resolve_command",
+    "launcher",
+    "aliases",
+    "overrides",
+    "extra",
+    "allow",
+];
+
+/// Fields accepted in a server's `container` table
+pub const CONTAINER_FIELDS: &[&str] = &["image", "runtime", "env", "mounts", "network", "pull"];
+
+/// Fields accepted in a server's `tools` table
+pub const TOOL_POLICY_FIELDS: &[&str] = &["allow", "deny", "auto_approve"];
+
+/// Fields accepted in `overrides.<tool>`
+pub const OVERRIDE_FIELDS: &[&str] = &["command", "args", "env", "url", "headers", "enabled"];
+
+/// Fields accepted on an HTTP server
+pub const HTTP_FIELDS: &[&str] = &[
+    "url",
+    "bearer_token",
+    "headers",
+    "auth",
+    "enabled",
+    "targets",
+    "expand",
+    "transport",
+    "startup_timeout_sec",
+    "tool_timeout_sec",
+    "tools",
+    "aliases",
+    "overrides",
+    "extra",
+    "allow",
+];
+
+impl<'de> Deserialize<'de> for ServerConfig {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        ServerSeed { name: None }.deserialize(deserializer)
+    }
+}
+
+/// Deserialize the server map, naming the server in every error
+fn deserialize_servers<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, ServerConfig>, D::Error> {
+    /// Visitor for the `[mcp.servers]` table
+    struct ServersVisitor;
+
+    impl<'de> Visitor<'de> for ServersVisitor {
+        type Value = HashMap<String, ServerConfig>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("a table of servers")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut servers = HashMap::new();
+            while let Some(name) = map.next_key::<String>()? {
+                let server = map.next_value_seed(ServerSeed { name: Some(&name) })?;
+                servers.insert(name, server);
+            }
+            Ok(servers)
+        }
+    }
+
+    deserializer.deserialize_map(ServersVisitor)
+}
+
+/// Deserializes one server, knowing its name for error messages
+struct ServerSeed<'a> {
+    /// Server name, when deserialized from the server map
+    name: Option<&'a str>,
+}
+
+impl<'de> DeserializeSeed<'de> for ServerSeed<'_> {
+    type Value = ServerConfig;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        let server = self
+            .name
+            .map_or_else(|| "Server".to_string(), |name| format!("Server '{name}'"));
+        let table = toml::Table::deserialize(deserializer)
+            .map_err(|e| D::Error::custom(format!("{server}: {e}")))?;
+
+        let launch: Vec<&str> = ["command", "package", "container"]
+            .into_iter()
+            .filter(|key| table.contains_key(*key))
+            .collect();
+        if let [first, second, ..] = launch.as_slice() {
+            return Err(D::Error::custom(format!(
+                "{server} has both '{first}' and '{second}'; a STDIO server is started by exactly \
+                 one of 'command', 'package' or 'container'"
+            )));
+        }
+        let stdio = !launch.is_empty();
+        let (kind, fields, other_kind, other_fields) = match (stdio, table.contains_key("url")) {
+            (true, true) => {
+                return Err(D::Error::custom(format!(
+                    "{server} has both '{}' and 'url'; a server must be STDIO ({}) or HTTP (url), \
+                     not both",
+                    launch[0], launch[0]
+                )));
+            }
+            (false, false) => {
+                return Err(D::Error::custom(format!(
+                    "{server} needs either 'command', 'package' or 'container' (stdio) or 'url' \
+                     (sse/http)"
+                )));
+            }
+            (true, false) => ("stdio", STDIO_FIELDS, "url", HTTP_FIELDS),
+            (false, true) => ("remote", HTTP_FIELDS, "command", STDIO_FIELDS),
+        };
+
+        // Fields of the other server kind are mistakes, not typos
+        if let Some(field) = table
+            .keys()
+            .find(|key| !fields.contains(&key.as_str()) && other_fields.contains(&key.as_str()))
+        {
+            return Err(D::Error::custom(format!(
+                "{server}: unknown field '{field}' for a {kind} server (it is only valid with \
+                 '{other_kind}')"
+            )));
+        }
+
+        let value = toml::Value::Table(table);
+        let result = if kind == "stdio" {
+            value.try_into().map(ServerConfig::Stdio)
+        } else {
+            value.try_into().map(ServerConfig::Http)
+        };
+        // The message ends with "\nin `field`" for nested errors
+        result.map_err(|e| {
+            let message = e.to_string();
+            D::Error::custom(format!(
+                "{server}: {}",
+                message.trim_end().replace('\n', " ")
+            ))
+        })
+    }
+}
+
 /// STDIO server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StdioServerConfig {
-    /// Command to execute (executable name or path)
+    /// Command to execute (executable name or path); empty for a package or
+    /// container
+    #[serde(default)]
     pub command: String,
 
+    /// Published package to run instead of a command, e.g.
+    /// `npm:@scope/name@1.4.2`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+
+    /// Container image to run instead of a command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerConfig>,
+
     /// Command arguments
     #[serde(default)]
     pub args: Vec<String>,
 
+    /// Working directory to start the server in
+    #[serde(default)]
+    pub cwd: Option<String>,
+
     /// Whether server is enabled
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -88,13 +404,230 @@ pub struct StdioServerConfig {
     #[serde(rename = "autoApprove", default)]
     pub auto_approve: Option<Vec<String>>,
 
-    /// Codex-specific: startup timeout in seconds
+    /// Seconds to wait for the server to start and list its tools
     #[serde(default)]
     pub startup_timeout_sec: Option<u32>,
 
-    /// Codex-specific: tool timeout in seconds
+    /// Seconds to wait for a single tool call
     #[serde(default)]
     pub tool_timeout_sec: Option<u32>,
+
+    /// Which of the server's tools are offered and which run unprompted
+    #[serde(default, skip_serializing_if = "ToolPolicy::is_empty")]
+    pub tools: ToolPolicy,
+
+    /// Expansion mode override for this server
+    #[serde(default)]
+    pub expand: Option<ExpandMode>,
+
+    /// Transport (must be `stdio` when set)
+    #[serde(default)]
+    pub transport: Option<Transport>,
+
+    /// Emit the command as its absolute path (overrides the setting)
+    #[serde(default)]
+    pub resolve_command: Option<bool>,
+
+    /// Start the server through a generated launcher (overrides the setting)
+    #[serde(default)]
+    pub launcher: Option<bool>,
+
+    /// Tool-specific names for this server, keyed by tool
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub aliases: HashMap<String, String>,
+
+    /// Fields that differ for one tool, keyed by tool
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub overrides: HashMap<String, ServerOverride>,
+
+    /// Raw fields emitted verbatim into one tool's server entry, keyed by
+    /// tool
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra: HashMap<String, toml::Table>,
+
+    /// Warning and info codes not to report for this server, e.g. `W001`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+
+    /// Keys not in the schema, reported by validation
+    #[serde(flatten, skip_serializing)]
+    pub unknown_fields: UnknownFields,
+}
+
+impl Default for StdioServerConfig {
+    fn default() -> Self {
+        Self {
+            command: String::new(),
+            package: None,
+            container: None,
+            args: Vec::new(),
+            cwd: None,
+            enabled: true,
+            targets: default_all_targets(),
+            env: None,
+            disabled: None,
+            auto_approve: None,
+            startup_timeout_sec: None,
+            tool_timeout_sec: None,
+            tools: ToolPolicy::default(),
+            expand: None,
+            transport: None,
+            resolve_command: None,
+            launcher: None,
+            aliases: HashMap::new(),
+            overrides: HashMap::new(),
+            extra: HashMap::new(),
+            allow: Vec::new(),
+            unknown_fields: UnknownFields::new(),
+        }
+    }
+}
+
+/// Fields of a server overridden for one tool
+///
+/// `args` and `command`/`url` replace the server's value; `env` and
+/// `headers` are merged into it, key by key.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServerOverride {
+    /// Command to run instead (STDIO only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// Arguments to pass instead (STDIO only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+
+    /// Environment variables to add or replace (STDIO only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<HashMap<String, String>>,
+
+    /// URL to connect to instead (HTTP only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// Headers to add or replace (HTTP only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub headers: Option<HashMap<String, String>>,
+
+    /// Whether the server is enabled for the tool
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+
+    /// Keys not in the schema, reported by validation
+    #[serde(flatten, skip_serializing)]
+    pub unknown_fields: UnknownFields,
+}
+
+/// Container image run as a STDIO server
+///
+/// Compiled into `<runtime> run -i --rm ... <image> <args...>`; environment
+/// variables are passed to the container by name, never by value.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContainerConfig {
+    /// Image to run, e.g. `ghcr.io/github/github-mcp-server`
+    #[serde(default)]
+    pub image: String,
+
+    /// Container CLI to run the image with
+    #[serde(default)]
+    pub runtime: ContainerRuntime,
+
+    /// Environment variables for the container, merged into the server's
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+
+    /// Bind mounts, `host:container` with an optional `:ro` or `:rw`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mounts: Vec<String>,
+
+    /// Network to attach the container to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<String>,
+
+    /// When to pull the image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pull: Option<PullPolicy>,
+
+    /// Keys not in the schema, reported by validation
+    #[serde(flatten, skip_serializing)]
+    pub unknown_fields: UnknownFields,
+}
+
+/// Container CLI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContainerRuntime {
+    /// `docker`
+    #[default]
+    Docker,
+    /// `podman`
+    Podman,
+}
+
+impl ContainerRuntime {
+    /// Executable name
+    #[must_use]
+    pub const fn command(self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+}
+
+/// When a container runtime pulls the image
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PullPolicy {
+    /// Before every start
+    Always,
+    /// Only when the image is not present
+    Missing,
+    /// Never; the image must be present
+    Never,
+}
+
+impl PullPolicy {
+    /// Value of `--pull`
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Always => "always",
+            Self::Missing => "missing",
+            Self::Never => "never",
+        }
+    }
+}
+
+/// Which of a server's tools a tool may use, and which need no approval
+///
+/// Translated into each tool's own mechanism; validation warns where a tool
+/// cannot express part of it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolPolicy {
+    /// Only these tools are offered; every tool when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+
+    /// Tools never offered
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+
+    /// Tools that run without asking for approval
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_approve: Vec<String>,
+
+    /// Keys not in the schema, reported by validation
+    #[serde(flatten, skip_serializing)]
+    pub unknown_fields: UnknownFields,
+}
+
+impl ToolPolicy {
+    /// Whether no list is set
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty() && self.auto_approve.is_empty()
+    }
 }
 
 /// HTTP server configuration
@@ -104,9 +637,19 @@ pub struct HttpServerConfig {
     pub url: String,
 
     /// Optional bearer token for authentication
+    ///
+    /// Shorthand for `auth = { type = "bearer", token = "..." }`.
     #[serde(default)]
     pub bearer_token: Option<String>,
 
+    /// Additional HTTP headers sent with every request
+    #[serde(default)]
+    pub headers: Option<HashMap<String, String>>,
+
+    /// Authentication scheme
+    #[serde(default)]
+    pub auth: Option<HttpAuth>,
+
     /// Whether server is enabled
     #[serde(default = "default_true")]
     pub enabled: bool,
@@ -114,6 +657,327 @@ pub struct HttpServerConfig {
     /// Target tools for this server
     #[serde(default = "default_all_targets")]
     pub targets: Vec<String>,
+
+    /// Expansion mode override for this server
+    #[serde(default)]
+    pub expand: Option<ExpandMode>,
+
+    /// Transport (`sse` or `http`, defaulting to streamable HTTP)
+    #[serde(default)]
+    pub transport: Option<Transport>,
+
+    /// Seconds to wait for the server to connect and list its tools
+    #[serde(default)]
+    pub startup_timeout_sec: Option<u32>,
+
+    /// Seconds to wait for a single tool call
+    #[serde(default)]
+    pub tool_timeout_sec: Option<u32>,
+
+    /// Which of the server's tools are offered and which run unprompted
+    #[serde(default, skip_serializing_if = "ToolPolicy::is_empty")]
+    pub tools: ToolPolicy,
+
+    /// Tool-specific names for this server, keyed by tool
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub aliases: HashMap<String, String>,
+
+    /// Fields that differ for one tool, keyed by tool
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub overrides: HashMap<String, ServerOverride>,
+
+    /// Raw fields emitted verbatim into one tool's server entry, keyed by
+    /// tool
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub extra: HashMap<String, toml::Table>,
+
+    /// Warning and info codes not to report for this server, e.g. `W001`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+
+    /// Keys not in the schema, reported by validation
+    #[serde(flatten, skip_serializing)]
+    pub unknown_fields: UnknownFields,
+}
+
+impl Default for HttpServerConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            bearer_token: None,
+            headers: None,
+            auth: None,
+            enabled: true,
+            targets: default_all_targets(),
+            expand: None,
+            transport: None,
+            startup_timeout_sec: None,
+            tool_timeout_sec: None,
+            tools: ToolPolicy::default(),
+            aliases: HashMap::new(),
+            overrides: HashMap::new(),
+            extra: HashMap::new(),
+            allow: Vec::new(),
+            unknown_fields: UnknownFields::new(),
+        }
+    }
+}
+
+/// How a tool talks to an MCP server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transport {
+    /// Local process over stdin/stdout
+    Stdio,
+    /// Remote server using HTTP with Server-Sent Events (legacy)
+    Sse,
+    /// Remote server using streamable HTTP
+    Http,
+}
+
+impl Transport {
+    /// Whether a tool can connect to servers over this transport
+    ///
+    /// Looked up in the [capability matrix](crate::config::capabilities).
+    #[must_use]
+    pub fn supported_by(self, tool: ToolName) -> bool {
+        capabilities(tool).is_none_or(|caps| caps.supports_transport(self))
+    }
+}
+
+impl std::fmt::Display for Transport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Stdio => write!(f, "stdio"),
+            Self::Sse => write!(f, "sse"),
+            Self::Http => write!(f, "http"),
+        }
+    }
+}
+
+/// Authentication for an HTTP server
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum HttpAuth {
+    /// `Authorization: Bearer <token>`
+    Bearer {
+        /// Bearer token
+        token: String,
+    },
+    /// `Authorization: Basic <base64(username:password)>`
+    Basic {
+        /// User name
+        username: String,
+        /// Password
+        password: String,
+    },
+    /// API key sent in a custom header, e.g. `X-API-Key`
+    Header {
+        /// Header name
+        name: String,
+        /// Header value
+        value: String,
+    },
+}
+
+impl HttpServerConfig {
+    /// Bearer token from either `bearer_token` or `auth`
+    #[must_use]
+    pub fn bearer(&self) -> Option<&str> {
+        match &self.auth {
+            Some(HttpAuth::Bearer { token }) => Some(token),
+            _ => self.bearer_token.as_deref(),
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Expansion mode for this server, falling back to the settings default
+    #[must_use]
+    pub fn expand_mode(&self, settings: Option<&Settings>) -> ExpandMode {
+        let own = match self {
+            Self::Stdio(stdio) => stdio.expand,
+            Self::Http(http) => http.expand,
+        };
+        own.or_else(|| settings.map(|s| s.expand))
+            .unwrap_or_default()
+    }
+
+    /// Diagnostic codes suppressed for this server
+    #[must_use]
+    pub fn allow(&self) -> &[String] {
+        match self {
+            Self::Stdio(stdio) => &stdio.allow,
+            Self::Http(http) => &http.allow,
+        }
+    }
+
+    /// Seconds to wait for the server to start, if set
+    #[must_use]
+    pub const fn startup_timeout_sec(&self) -> Option<u32> {
+        match self {
+            Self::Stdio(stdio) => stdio.startup_timeout_sec,
+            Self::Http(http) => http.startup_timeout_sec,
+        }
+    }
+
+    /// Seconds to wait for a tool call, if set
+    #[must_use]
+    pub const fn tool_timeout_sec(&self) -> Option<u32> {
+        match self {
+            Self::Stdio(stdio) => stdio.tool_timeout_sec,
+            Self::Http(http) => http.tool_timeout_sec,
+        }
+    }
+
+    /// Tool allow, deny and auto-approve lists
+    #[must_use]
+    pub const fn tools(&self) -> &ToolPolicy {
+        match self {
+            Self::Stdio(stdio) => &stdio.tools,
+            Self::Http(http) => &http.tools,
+        }
+    }
+
+    /// Tool-specific names, keyed by tool
+    #[must_use]
+    pub const fn aliases(&self) -> &HashMap<String, String> {
+        match self {
+            Self::Stdio(stdio) => &stdio.aliases,
+            Self::Http(http) => &http.aliases,
+        }
+    }
+
+    /// Name the server is emitted under for a tool: its alias, if set
+    #[must_use]
+    pub fn name_for<'a>(&'a self, name: &'a str, tool: ToolName) -> &'a str {
+        self.aliases()
+            .get(tool.as_str())
+            .map_or(name, String::as_str)
+    }
+
+    /// Per-tool field overrides, keyed by tool
+    #[must_use]
+    pub const fn overrides(&self) -> &HashMap<String, ServerOverride> {
+        match self {
+            Self::Stdio(stdio) => &stdio.overrides,
+            Self::Http(http) => &http.overrides,
+        }
+    }
+
+    /// Raw passthrough fields, keyed by tool
+    #[must_use]
+    pub const fn extra(&self) -> &HashMap<String, toml::Table> {
+        match self {
+            Self::Stdio(stdio) => &stdio.extra,
+            Self::Http(http) => &http.extra,
+        }
+    }
+
+    /// Raw fields to emit into a tool's entry for this server
+    #[must_use]
+    pub fn extra_for(&self, tool: ToolName) -> Option<&toml::Table> {
+        self.extra().get(tool.as_str())
+    }
+
+    /// Mutable per-tool field overrides
+    pub const fn overrides_mut(&mut self) -> &mut HashMap<String, ServerOverride> {
+        match self {
+            Self::Stdio(stdio) => &mut stdio.overrides,
+            Self::Http(http) => &mut http.overrides,
+        }
+    }
+
+    /// The server as a tool sees it, with that tool's overrides applied
+    ///
+    /// Overrides of fields the server kind doesn't have (`url` on a STDIO
+    /// server) are ignored here and reported by validation.
+    #[must_use]
+    pub fn for_tool(&self, tool: ToolName) -> Self {
+        let mut server = self.clone();
+        let Some(over) = self.overrides().get(tool.as_str()) else {
+            return server;
+        };
+
+        match &mut server {
+            Self::Stdio(stdio) => {
+                if let Some(command) = &over.command {
+                    stdio.command.clone_from(command);
+                }
+                if let Some(args) = &over.args {
+                    stdio.args.clone_from(args);
+                }
+                if let Some(env) = &over.env {
+                    stdio
+                        .env
+                        .get_or_insert_with(HashMap::new)
+                        .extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
+                }
+                if let Some(enabled) = over.enabled {
+                    stdio.enabled = enabled;
+                }
+            }
+            Self::Http(http) => {
+                if let Some(url) = &over.url {
+                    http.url.clone_from(url);
+                }
+                if let Some(headers) = &over.headers {
+                    http.headers
+                        .get_or_insert_with(HashMap::new)
+                        .extend(headers.iter().map(|(k, v)| (k.clone(), v.clone())));
+                }
+                if let Some(enabled) = over.enabled {
+                    http.enabled = enabled;
+                }
+            }
+        }
+        server
+    }
+
+    /// Target tools as written, `["all"]` by default
+    #[must_use]
+    pub fn targets(&self) -> &[String] {
+        match self {
+            Self::Stdio(stdio) => &stdio.targets,
+            Self::Http(http) => &http.targets,
+        }
+    }
+
+    /// Whether the server is enabled
+    #[must_use]
+    pub const fn enabled(&self) -> bool {
+        match self {
+            Self::Stdio(stdio) => stdio.enabled,
+            Self::Http(http) => http.enabled,
+        }
+    }
+
+    /// Transport set with the `transport` field, if any
+    #[must_use]
+    pub const fn explicit_transport(&self) -> Option<Transport> {
+        match self {
+            Self::Stdio(stdio) => stdio.transport,
+            Self::Http(http) => http.transport,
+        }
+    }
+
+    /// Transport for this server
+    ///
+    /// Falls back to inferring it from the fields present: `command` means
+    /// STDIO, `url` means streamable HTTP.
+    #[must_use]
+    pub const fn transport(&self) -> Transport {
+        match (self.explicit_transport(), self) {
+            (Some(transport), _) => transport,
+            (None, Self::Stdio(_)) => Transport::Stdio,
+            (None, Self::Http(_)) => Transport::Http,
+        }
+    }
+}
+
+/// Default for `unknown_keys`: typos are errors
+const fn default_unknown_keys() -> LintLevel {
+    LintLevel::Error
 }
 
 /// Default value for boolean fields: true
@@ -218,6 +1082,24 @@ mod tests {
         assert_eq!(targets[0], "all");
     }
 
+    #[test]
+    fn test_expand_mode_server_overrides_settings() {
+        let settings = Settings {
+            expand: ExpandMode::Runtime,
+            ..Settings::default()
+        };
+
+        let inherits = ServerConfig::Stdio(StdioServerConfig::default());
+        assert_eq!(inherits.expand_mode(Some(&settings)), ExpandMode::Runtime);
+        assert_eq!(inherits.expand_mode(None), ExpandMode::Compile);
+
+        let own = ServerConfig::Http(HttpServerConfig {
+            expand: Some(ExpandMode::Compile),
+            ..HttpServerConfig::default()
+        });
+        assert_eq!(own.expand_mode(Some(&settings)), ExpandMode::Compile);
+    }
+
     #[test]
     fn test_tool_name_from_str() {
         assert_eq!(
@@ -256,4 +1138,174 @@ mod tests {
         assert!(tools.contains(&ToolName::Codex));
         assert!(!tools.contains(&ToolName::All));
     }
+
+    fn parse_servers(toml_str: &str) -> Result<McpConfig, String> {
+        toml::from_str(toml_str).map_err(|e| e.message().to_string())
+    }
+
+    #[test]
+    fn test_server_kind_from_fields() {
+        let mcp = parse_servers(
+            r#"
+[servers.local]
+command = "npx"
+
+[servers.remote]
+url = "https://example.com/mcp"
+"#,
+        )
+        .unwrap();
+
+        assert!(matches!(mcp.servers["local"], ServerConfig::Stdio(_)));
+        assert!(matches!(mcp.servers["remote"], ServerConfig::Http(_)));
+    }
+
+    #[test]
+    fn test_server_with_both_command_and_url() {
+        let err = parse_servers(
+            r#"
+[servers.both]
+command = "npx"
+url = "https://example.com/mcp"
+"#,
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Server 'both' has both 'command' and 'url'"));
+    }
+
+    #[test]
+    fn test_server_with_neither_command_nor_url() {
+        let err = parse_servers("[servers.empty]\nargs = [\"x\"]\n").unwrap_err();
+        assert_eq!(
+            err,
+            "Server 'empty' needs either 'command', 'package' or 'container' (stdio) or 'url' \
+             (sse/http)"
+        );
+    }
+
+    #[test]
+    fn test_container_server() {
+        let servers = parse_servers(
+            r#"
+[servers.github]
+args = ["stdio"]
+
+[servers.github.container]
+image = "ghcr.io/github/github-mcp-server"
+runtime = "podman"
+pull = "missing"
+"#,
+        )
+        .unwrap();
+        let ServerConfig::Stdio(stdio) = &servers.servers["github"] else {
+            panic!("expected stdio server");
+        };
+        let container = stdio.container.as_ref().unwrap();
+        assert!(stdio.command.is_empty());
+        assert_eq!(container.runtime, ContainerRuntime::Podman);
+        assert_eq!(container.pull, Some(PullPolicy::Missing));
+
+        let err = parse_servers(
+            r#"
+[servers.both]
+command = "npx"
+container = { image = "mcp/github" }
+"#,
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Server 'both' has both 'command' and 'container'"));
+    }
+
+    #[test]
+    fn test_package_server() {
+        let servers = parse_servers("[servers.git]\npackage = \"pypi:mcp-server-git\"\n").unwrap();
+        let ServerConfig::Stdio(stdio) = &servers.servers["git"] else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(stdio.package.as_deref(), Some("pypi:mcp-server-git"));
+
+        let err = parse_servers(
+            r#"
+[servers.both]
+package = "npm:server"
+url = "https://example.com/mcp"
+"#,
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Server 'both' has both 'package' and 'url'"));
+    }
+
+    #[test]
+    fn test_server_field_of_other_kind() {
+        let err = parse_servers(
+            r#"
+[servers.local]
+command = "npx"
+bearer_token = "token"
+"#,
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            "Server 'local': unknown field 'bearer_token' for a stdio server (it is only valid \
+             with 'url')"
+        );
+    }
+
+    #[test]
+    fn test_server_field_wrong_type() {
+        let err = parse_servers("[servers.local]\ncommand = \"npx\"\nargs = \"-y\"\n").unwrap_err();
+        assert_eq!(
+            err,
+            "Server 'local': invalid type: string \"-y\", expected a sequence in `args`"
+        );
+    }
+
+    #[test]
+    fn test_settings_fields_match_schema() {
+        // Optional fields are only serialized when set
+        let settings = Settings {
+            search_path: Some("/usr/bin".to_string()),
+            ..Settings::default()
+        };
+        let serialized = toml::to_string(&settings).unwrap();
+        let table: toml::Table = toml::from_str(&serialized).unwrap();
+        let keys: Vec<&str> = table.keys().map(String::as_str).collect();
+        assert_eq!(keys, SETTINGS_FIELDS);
+    }
+
+    #[test]
+    fn test_server_for_tool_applies_overrides() {
+        let mcp = parse_servers(
+            r#"
+[servers.github]
+command = "github-mcp"
+args = ["stdio"]
+env = { LOG = "info", TOKEN = "abc" }
+
+[servers.github.overrides.codex]
+args = ["stdio", "--read-only"]
+env = { LOG = "debug" }
+
+[servers.github.overrides.cursor]
+enabled = false
+"#,
+        )
+        .unwrap();
+        let server = &mcp.servers["github"];
+
+        let ServerConfig::Stdio(codex) = server.for_tool(ToolName::Codex) else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(codex.args, ["stdio", "--read-only"]);
+        let env = codex.env.unwrap();
+        assert_eq!(env["LOG"], "debug");
+        assert_eq!(env["TOKEN"], "abc");
+
+        assert!(!server.for_tool(ToolName::Cursor).enabled());
+        let ServerConfig::Stdio(opencode) = server.for_tool(ToolName::Opencode) else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(opencode.args, ["stdio"]);
+    }
 }
@@ -0,0 +1,261 @@
+//! Source-located diagnostics
+//!
+//! TOML syntax errors, schema errors and validation errors are all turned
+//! into [`Diagnostic`]s pointing at spans of the config file. They render as
+//! a source snippet with the offending text underlined, or serialize to JSON
+//! for editors and CI.
+
+pub mod source;
+
+pub use source::SourceFile;
+
+use crate::config::ValidationError;
+use serde::Serialize;
+use std::{fmt::Write as _, ops::Range};
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Fails the command
+    Error,
+    /// Reported, but does not fail the command unless warnings are denied
+    Warning,
+    /// Informational, never fails the command
+    Info,
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Info => write!(f, "info"),
+        }
+    }
+}
+
+/// A span of the config file a diagnostic refers to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Label {
+    /// Byte range in the file
+    #[serde(skip)]
+    pub span: Range<usize>,
+    /// Whether this is the main location (underlined with `^`)
+    pub primary: bool,
+    /// Note shown next to the underline
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// 1-based start line
+    pub line: usize,
+    /// 1-based start column, in characters
+    pub column: usize,
+    /// 1-based end line
+    pub end_line: usize,
+    /// 1-based column just past the end, in characters
+    pub end_column: usize,
+}
+
+/// A message about the config file, with the locations it refers to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    /// Error, warning or info
+    pub severity: Severity,
+    /// Stable code, such as `W001`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+    /// What is wrong
+    pub message: String,
+    /// Config file the labels point into
+    pub file: String,
+    /// Locations, primary first; empty when none could be found
+    pub labels: Vec<Label>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic without locations
+    #[must_use]
+    pub fn new(severity: Severity, message: impl Into<String>, source: &SourceFile) -> Self {
+        Self {
+            severity,
+            code: None,
+            message: message.into(),
+            file: source.path().display().to_string(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Add a location
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - File the span points into
+    /// * `span` - Byte range of the location
+    /// * `primary` - Whether this is the main location
+    /// * `message` - Optional note shown next to the underline
+    #[must_use]
+    pub fn with_label(
+        mut self,
+        source: &SourceFile,
+        span: Range<usize>,
+        primary: bool,
+        message: Option<String>,
+    ) -> Self {
+        let (line, column) = source.position(span.start);
+        let (end_line, end_column) = source.position(span.end.max(span.start));
+        self.labels.push(Label {
+            span,
+            primary,
+            message,
+            line,
+            column,
+            end_line,
+            end_column,
+        });
+        self
+    }
+
+    /// Locate a validation error in the source
+    ///
+    /// The primary label is the error's location (or, failing that, its
+    /// context); each related location becomes a secondary label.
+    #[must_use]
+    pub fn from_validation_error(error: &ValidationError, source: &SourceFile) -> Self {
+        let mut diagnostic = Self::new(error.severity, error.to_string(), source);
+        diagnostic.code = error.code.map(str::to_string);
+
+        let primary = error.location.as_deref().or(error.context.as_deref());
+        if let Some(span) = primary.and_then(|path| source.span_of(path)) {
+            diagnostic = diagnostic.with_label(source, span, true, None);
+        }
+        for (path, note) in &error.related {
+            if let Some(span) = source.span_of(path) {
+                diagnostic = diagnostic.with_label(source, span, false, Some(note.clone()));
+            }
+        }
+
+        diagnostic
+    }
+
+    /// Location of the primary label as `file:line:column`
+    #[must_use]
+    pub fn location(&self) -> String {
+        self.labels
+            .iter()
+            .find(|label| label.primary)
+            .or_else(|| self.labels.first())
+            .map_or_else(
+                || self.file.clone(),
+                |label| format!("{}:{}:{}", self.file, label.line, label.column),
+            )
+    }
+
+    /// Render the diagnostic with a source snippet
+    ///
+    /// ```text
+    /// warning[W001]: Duplicate target 'cursor'
+    ///  --> config.toml:3:30
+    ///   |
+    /// 3 | default_targets = ["cursor", "cursor"]
+    ///   |                              ^^^^^^^^
+    ///   |                    -------- first listed here
+    /// ```
+    #[must_use]
+    pub fn render(&self, source: &SourceFile) -> String {
+        let code = self
+            .code
+            .as_ref()
+            .map_or_else(String::new, |code| format!("[{code}]"));
+        let mut out = format!("{}{code}: {}\n", self.severity, self.message);
+        if self.labels.is_empty() {
+            return out;
+        }
+
+        let mut lines: Vec<usize> = self.labels.iter().map(|label| label.line).collect();
+        lines.sort_unstable();
+        lines.dedup();
+
+        let width = lines.last().map_or(1, |line| line.to_string().len());
+        let gutter = " ".repeat(width);
+
+        let _ = writeln!(out, "{gutter}--> {}", self.location());
+        let _ = writeln!(out, "{gutter} |");
+        for line in lines {
+            let text = source.line_text(line);
+            let _ = writeln!(out, "{line:>width$} | {text}");
+
+            for label in self.labels.iter().filter(|label| label.line == line) {
+                let end = if label.end_line == line {
+                    label.end_column
+                } else {
+                    text.chars().count() + 1
+                };
+                let marker = if label.primary { "^" } else { "-" };
+                let underline = marker.repeat(end.saturating_sub(label.column).max(1));
+                let padding = " ".repeat(label.column - 1);
+                let note = label
+                    .message
+                    .as_deref()
+                    .map_or_else(String::new, |message| format!(" {message}"));
+                let _ = writeln!(out, "{gutter} | {padding}{underline}{note}");
+            }
+        }
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str =
+        "[settings]\nversion = \"1.0\"\ndefault_targets = [\"cursor\", \"cursor\"]\n";
+
+    #[test]
+    fn test_render_with_related_label() {
+        let source = SourceFile::new("config.toml", CONFIG);
+        let error =
+            ValidationError::with_context("Duplicate target 'cursor'", "settings.default_targets")
+                .with_code("W001")
+                .at("settings.default_targets[1]")
+                .with_related("settings.default_targets[0]", "first listed here");
+
+        let diagnostic = Diagnostic::from_validation_error(&error, &source);
+        assert_eq!(diagnostic.location(), "config.toml:3:30");
+        assert_eq!(
+            diagnostic.render(&source),
+            concat!(
+                "warning[W001]: settings.default_targets: Duplicate target 'cursor'\n",
+                " --> config.toml:3:30\n",
+                "  |\n",
+                "3 | default_targets = [\"cursor\", \"cursor\"]\n",
+                "  |                              ^^^^^^^^\n",
+                "  |                    -------- first listed here\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_json_form() {
+        let source = SourceFile::new("config.toml", CONFIG);
+        let error = ValidationError::with_context("Unsupported version", "settings.version")
+            .with_code("E002");
+
+        let diagnostic = Diagnostic::from_validation_error(&error, &source);
+        let json = serde_json::to_value(&diagnostic).unwrap();
+        assert_eq!(json["severity"], "error");
+        assert_eq!(json["code"], "E002");
+        assert_eq!(json["file"], "config.toml");
+        assert_eq!(json["labels"][0]["line"], 2);
+        assert_eq!(json["labels"][0]["column"], 1);
+        assert_eq!(json["labels"][0]["end_column"], 16);
+    }
+
+    #[test]
+    fn test_render_without_location() {
+        let source = SourceFile::new("config.toml", CONFIG);
+        let diagnostic = Diagnostic::new(Severity::Error, "No servers", &source);
+        assert_eq!(diagnostic.render(&source), "error: No servers\n");
+    }
+}
@@ -0,0 +1,263 @@
+//! Config file source text and span lookup
+
+use std::{
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+};
+use toml_edit::{Document, Item, Key, TableLike, Value};
+
+/// A config file's text, indexed for line/column lookup and for finding
+/// the span of a dotted config path such as `mcp.servers.github.args[0]`
+#[derive(Debug)]
+pub struct SourceFile {
+    /// File the text was read from
+    path: PathBuf,
+    /// File contents
+    text: String,
+    /// Byte offset at which each line starts
+    line_starts: Vec<usize>,
+    /// Parsed document with spans, if the text is valid TOML
+    document: Option<Document<String>>,
+}
+
+impl SourceFile {
+    /// Index source text
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File the text came from, shown in diagnostics
+    /// * `text` - File contents
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>, text: impl Into<String>) -> Self {
+        let text = text.into();
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        let document = Document::parse(text.clone()).ok();
+
+        Self {
+            path: path.into(),
+            text,
+            line_starts,
+            document,
+        }
+    }
+
+    /// Read and index a file
+    ///
+    /// An unreadable file yields an empty source; diagnostics for it are
+    /// still rendered, just without snippets.
+    #[must_use]
+    pub fn load(path: &Path) -> Self {
+        Self::new(path, fs::read_to_string(path).unwrap_or_default())
+    }
+
+    /// File the text was read from
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// File contents
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// 1-based line and column (in characters) of a byte offset
+    #[must_use]
+    pub fn position(&self, offset: usize) -> (usize, usize) {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let column = self
+            .text
+            .get(line_start..offset)
+            .map_or(0, |prefix| prefix.chars().count());
+        (line, column + 1)
+    }
+
+    /// Text of a 1-based line, without its line ending
+    #[must_use]
+    pub fn line_text(&self, line: usize) -> &str {
+        let Some(&start) = self.line_starts.get(line.wrapping_sub(1)) else {
+            return "";
+        };
+        let end = self
+            .line_starts
+            .get(line)
+            .map_or(self.text.len(), |&next| next - 1);
+        self.text[start..end].trim_end_matches('\r')
+    }
+
+    /// Span of the item at a dotted config path
+    ///
+    /// Tables with a header span the header; other items span from their
+    /// key to the end of their value. Array elements are addressed as
+    /// `args[0]`, and keys containing dots are matched as written. When only
+    /// a prefix of the path exists, the span of the deepest item found is
+    /// returned.
+    #[must_use]
+    pub fn span_of(&self, path: &str) -> Option<Range<usize>> {
+        self.lookup(path).and_then(|found| found.span)
+    }
+
+    /// Literal string value at a dotted config path
+    #[must_use]
+    pub fn string_at(&self, path: &str) -> Option<&str> {
+        self.lookup(path)
+            .filter(|found| found.complete)
+            .and_then(|found| found.value)
+            .and_then(Value::as_str)
+    }
+
+    /// Walk a dotted path as far as it goes
+    fn lookup(&self, path: &str) -> Option<Found<'_>> {
+        let document = self.document.as_ref()?;
+        let segments: Vec<&str> = path.split('.').collect();
+
+        let mut table: Option<&dyn TableLike> = document.as_item().as_table_like();
+        let mut found = Found::default();
+        let mut i = 0;
+
+        'segments: while i < segments.len() {
+            let Some(current) = table else { break };
+
+            // Longest match first, so a key like "a.b" wins over "a"
+            for j in (i + 1..=segments.len()).rev() {
+                let joined = segments[i..j].join(".");
+                let (name, indices) = split_indices(&joined);
+                let Some((key, item)) = current.get_key_value(name) else {
+                    continue;
+                };
+
+                found.span = item_span(key, item).or(found.span);
+                found.value = item.as_value();
+                table = item.as_table_like();
+
+                for index in indices {
+                    let Some(element) = found
+                        .value
+                        .and_then(Value::as_array)
+                        .and_then(|a| a.get(index))
+                    else {
+                        return Some(found);
+                    };
+                    found.span = element.span().or(found.span);
+                    found.value = Some(element);
+                    table = element.as_inline_table().map(|t| t as &dyn TableLike);
+                }
+
+                i = j;
+                continue 'segments;
+            }
+            break;
+        }
+
+        found.complete = i == segments.len();
+        Some(found)
+    }
+}
+
+/// Result of walking a config path
+#[derive(Default)]
+struct Found<'a> {
+    /// Span of the deepest item reached
+    span: Option<Range<usize>>,
+    /// Value of the deepest item reached, if it is a value
+    value: Option<&'a Value>,
+    /// Whether the whole path was resolved
+    complete: bool,
+}
+
+/// Span of a keyed item
+fn item_span(key: &Key, item: &Item) -> Option<Range<usize>> {
+    if let Item::Table(table) = item {
+        if let Some(span) = table.span() {
+            return Some(span);
+        }
+    }
+    match (key.span(), item.span()) {
+        (Some(key), Some(value)) => Some(key.start..value.end.max(key.end)),
+        (key, value) => key.or(value),
+    }
+}
+
+/// Split `name[1][2]` into `name` and its indices
+fn split_indices(segment: &str) -> (&str, Vec<usize>) {
+    let Some(open) = segment.find('[') else {
+        return (segment, Vec::new());
+    };
+    let indices = segment[open..]
+        .split(['[', ']'])
+        .filter_map(|index| index.parse().ok())
+        .collect();
+    (&segment[..open], indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"[settings]
+version = "1.0"
+
+[mcp.servers.github]
+command = "npx"
+args = ["-y", "server"]
+env = { TOKEN = "abc" }
+"#;
+
+    fn source() -> SourceFile {
+        SourceFile::new("config.toml", CONFIG)
+    }
+
+    fn text_of(source: &SourceFile, path: &str) -> String {
+        source
+            .span_of(path)
+            .map(|span| CONFIG[span].to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_position() {
+        let source = source();
+        assert_eq!(source.position(0), (1, 1));
+        assert_eq!(source.position(CONFIG.find("version").unwrap()), (2, 1));
+        assert_eq!(source.position(CONFIG.find("\"1.0\"").unwrap()), (2, 11));
+        assert_eq!(source.line_text(2), "version = \"1.0\"");
+    }
+
+    #[test]
+    fn test_span_of_paths() {
+        let source = source();
+        assert_eq!(text_of(&source, "settings.version"), "version = \"1.0\"");
+        assert_eq!(
+            text_of(&source, "mcp.servers.github"),
+            "[mcp.servers.github]"
+        );
+        assert_eq!(text_of(&source, "mcp.servers.github.args[1]"), "\"server\"");
+        assert_eq!(
+            text_of(&source, "mcp.servers.github.env.TOKEN"),
+            "TOKEN = \"abc\""
+        );
+
+        // Missing leaves fall back to the deepest item found
+        assert_eq!(
+            text_of(&source, "mcp.servers.github.targets"),
+            "[mcp.servers.github]"
+        );
+    }
+
+    #[test]
+    fn test_string_at() {
+        let source = source();
+        assert_eq!(
+            source.string_at("mcp.servers.github.env.TOKEN"),
+            Some("abc")
+        );
+        assert_eq!(source.string_at("mcp.servers.github.args[0]"), Some("-y"));
+        assert_eq!(source.string_at("mcp.servers.github.env.MISSING"), None);
+    }
+}
@@ -1,12 +1,19 @@
 //! Health check and diagnostics module.
 
+use multi_agent_config::{
+    config::{lint_credentials, parse_config_file},
+    file_ops::{PermissionIssue, check_private, make_private, sensitive_paths},
+};
 use std::path::Path;
 use workhelix_cli_common::DoctorCheck;
 
 /// Run tool-specific health checks.
 ///
+/// With `fix`, group/world access is removed from files that should be
+/// private before they are checked.
+///
 /// Returns a vector of health check results.
-pub fn tool_specific_checks(config_path: &Path) -> Vec<DoctorCheck> {
+pub fn tool_specific_checks(config_path: &Path, fix: bool) -> Vec<DoctorCheck> {
     let mut checks = Vec::new();
 
     // Check if config file exists
@@ -21,6 +28,7 @@ pub fn tool_specific_checks(config_path: &Path) -> Vec<DoctorCheck> {
             Ok(content) => {
                 if toml::from_str::<toml::Value>(&content).is_ok() {
                     checks.push(DoctorCheck::pass("Config is valid TOML"));
+                    checks.extend(credential_checks(config_path));
                 } else {
                     checks.push(DoctorCheck::fail(
                         "Config validation",
@@ -45,5 +53,77 @@ pub fn tool_specific_checks(config_path: &Path) -> Vec<DoctorCheck> {
         ));
     }
 
+    checks.extend(permission_checks(config_path, fix));
+
+    checks
+}
+
+/// Check that the config, state and backups are private to the current user.
+fn permission_checks(config_path: &Path, fix: bool) -> Vec<DoctorCheck> {
+    let mut checks = Vec::new();
+
+    for path in sensitive_paths(config_path) {
+        let mut issues = match check_private(&path) {
+            Ok(issues) => issues,
+            Err(e) => {
+                checks.push(DoctorCheck::fail(
+                    "File permissions",
+                    format!("Cannot inspect {}: {e}", path.display()),
+                ));
+                continue;
+            }
+        };
+
+        if fix && issues.iter().any(PermissionIssue::is_fixable) {
+            match make_private(&path) {
+                Ok(()) => {
+                    checks.push(DoctorCheck::pass(format!(
+                        "Restricted permissions on {}",
+                        path.display()
+                    )));
+                    issues.retain(|issue| !issue.is_fixable());
+                }
+                Err(e) => checks.push(DoctorCheck::fail(
+                    "File permissions",
+                    format!("Cannot restrict {}: {e}", path.display()),
+                )),
+            }
+        }
+
+        for issue in issues {
+            let hint = if issue.is_fixable() {
+                " - run 'multi-agent-config doctor --fix'"
+            } else {
+                ""
+            };
+            checks.push(DoctorCheck::fail(
+                "File permissions",
+                format!("{}: {issue}{hint}", path.display()),
+            ));
+        }
+    }
+
+    if checks.is_empty() {
+        checks.push(DoctorCheck::pass("Config, state and backups are private"));
+    }
+
     checks
 }
+
+/// Report literal values in the config that look like credentials.
+fn credential_checks(config_path: &Path) -> Vec<DoctorCheck> {
+    // Schema problems are reported by 'validate'
+    let Ok(config) = parse_config_file(config_path) else {
+        return Vec::new();
+    };
+
+    let findings = lint_credentials(&config);
+    if findings.is_empty() {
+        return vec![DoctorCheck::pass("No hard-coded credentials in config")];
+    }
+
+    findings
+        .iter()
+        .map(|finding| DoctorCheck::fail("Hard-coded credential", finding.to_string()))
+        .collect()
+}
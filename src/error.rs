@@ -1,6 +1,7 @@
 //! Error types for multi-agent-config
 
-use std::path::PathBuf;
+use crate::diagnostics::{Diagnostic, Severity, SourceFile};
+use std::{ops::Range, path::PathBuf};
 use thiserror::Error;
 
 // Exit codes matching specification
@@ -43,9 +44,17 @@ pub enum MultiAgentError {
     #[error("File operation error: {0}")]
     FileOpError(#[from] crate::file_ops::writer::FileOpError),
 
+    /// Encrypted secrets file error
+    #[error("Secrets error: {0}")]
+    Secrets(#[from] crate::secrets::SecretsError),
+
     /// CLI argument error
     #[error("CLI error: {0}")]
     CliError(String),
+
+    /// Lock file or package registry error
+    #[error("Package error: {0}")]
+    PackageError(String),
 }
 
 impl MultiAgentError {
@@ -65,7 +74,10 @@ impl MultiAgentError {
             Self::ExpansionError(_) => EXIT_VALIDATION_ERROR,
             Self::TransformError(_) => EXIT_VALIDATION_ERROR,
             Self::FileOpError(_) => EXIT_FILE_ERROR,
+            Self::Secrets(crate::secrets::SecretsError::Io(_)) => EXIT_FILE_ERROR,
+            Self::Secrets(_) => EXIT_VALIDATION_ERROR,
             Self::CliError(_) => EXIT_VALIDATION_ERROR,
+            Self::PackageError(_) => EXIT_FILE_ERROR,
         }
     }
 
@@ -87,7 +99,31 @@ impl MultiAgentError {
                     path.display()
                 )
             }
-            Self::Config(ConfigError::ParseError { message, line }) => {
+            Self::Config(ConfigError::ParseError {
+                message,
+                file: Some(file),
+                span: Some(span),
+                syntax,
+                ..
+            }) => {
+                let source = SourceFile::load(file);
+                let diagnostic = Diagnostic::new(Severity::Error, message, &source).with_label(
+                    &source,
+                    span.clone(),
+                    true,
+                    None,
+                );
+                // Valid TOML that doesn't match the schema has its own message
+                if *syntax {
+                    format!(
+                        "{}\nSuggestion: Check TOML syntax at the indicated location.",
+                        diagnostic.render(&source)
+                    )
+                } else {
+                    diagnostic.render(&source)
+                }
+            }
+            Self::Config(ConfigError::ParseError { message, line, .. }) => {
                 format!(
                     "Error: Parse error at line {line}: {message}\n\nSuggestion: Check TOML \
                      syntax at the indicated line."
@@ -122,6 +158,15 @@ pub enum ConfigError {
         message: String,
         /// Line number where error occurred
         line: usize,
+        /// 1-based column where the error occurred, 0 if unknown
+        column: usize,
+        /// File the error is in, if known
+        file: Option<PathBuf>,
+        /// Byte range of the offending text, if known
+        span: Option<Range<usize>>,
+        /// Whether the TOML is malformed, rather than valid TOML that
+        /// doesn't match the schema
+        syntax: bool,
     },
 
     /// Schema validation error
@@ -143,6 +188,59 @@ impl ConfigError {
         Self::ParseError {
             message: message.into(),
             line,
+            column: 0,
+            file: None,
+            span: None,
+            syntax: true,
+        }
+    }
+
+    /// Create a parse error pointing at a span of a file
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Error message
+    /// * `source` - File the error is in
+    /// * `span` - Byte range of the offending text
+    pub fn parse_error_at(
+        message: impl Into<String>,
+        source: &SourceFile,
+        span: Range<usize>,
+    ) -> Self {
+        Self::spanned(message, source, span, true)
+    }
+
+    /// Create an error for valid TOML that doesn't match the schema,
+    /// pointing at a span of a file
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - Error message
+    /// * `source` - File the error is in
+    /// * `span` - Byte range of the offending text
+    pub fn schema_error_at(
+        message: impl Into<String>,
+        source: &SourceFile,
+        span: Range<usize>,
+    ) -> Self {
+        Self::spanned(message, source, span, false)
+    }
+
+    /// Parse error at a span, for malformed TOML or a schema mismatch
+    fn spanned(
+        message: impl Into<String>,
+        source: &SourceFile,
+        span: Range<usize>,
+        syntax: bool,
+    ) -> Self {
+        let (line, column) = source.position(span.start);
+        Self::ParseError {
+            message: message.into(),
+            line,
+            column,
+            file: Some(source.path().to_path_buf()),
+            span: Some(span),
+            syntax,
         }
     }
 
@@ -210,6 +308,30 @@ mod tests {
         assert!(formatted.contains("Suggestion"));
     }
 
+    #[test]
+    fn test_format_with_suggestion_only_hints_syntax_for_syntax_errors() {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut file, b"[mcp.servers.x]\ncommand = \"npx\"\n").unwrap();
+        let source = SourceFile::load(file.path());
+
+        let syntax =
+            MultiAgentError::Config(ConfigError::parse_error_at("expected `=`", &source, 16..23));
+        assert!(
+            syntax
+                .format_with_suggestion()
+                .contains("Check TOML syntax")
+        );
+
+        let schema = MultiAgentError::Config(ConfigError::schema_error_at(
+            "Server 'x' has both 'command' and 'url'",
+            &source,
+            16..23,
+        ));
+        let formatted = schema.format_with_suggestion();
+        assert!(formatted.contains("has both 'command' and 'url'"));
+        assert!(!formatted.contains("Suggestion"));
+    }
+
     #[test]
     fn test_file_not_found_display() {
         let err = ConfigError::FileNotFound(PathBuf::from("/path/to/config.toml"));
@@ -231,6 +353,21 @@ mod tests {
         assert_eq!(format!("{err}"), "Parse error at line 42: invalid syntax");
     }
 
+    #[test]
+    fn test_parse_error_at_span() {
+        let source = SourceFile::new("config.toml", "[settings]\nversion = 1.0\n");
+        let err =
+            ConfigError::parse_error_at("invalid type: float, expected a string", &source, 21..24);
+        match &err {
+            ConfigError::ParseError { line, column, .. } => assert_eq!((*line, *column), (2, 11)),
+            _ => panic!("Expected ParseError variant"),
+        }
+        assert_eq!(
+            format!("{err}"),
+            "Parse error at line 2: invalid type: float, expected a string"
+        );
+    }
+
     #[test]
     fn test_validation_error_display() {
         let err = ConfigError::validation("missing required field");
@@ -241,7 +378,7 @@ mod tests {
     fn test_parse_error_constructor() {
         let err = ConfigError::parse_error("test message", 10);
         match err {
-            ConfigError::ParseError { message, line } => {
+            ConfigError::ParseError { message, line, .. } => {
                 assert_eq!(message, "test message");
                 assert_eq!(line, 10);
             }
@@ -62,6 +62,12 @@ pub struct Expander {
     shell_env: HashMap<String, String>,
     /// Warnings collected during expansion
     warnings: Vec<String>,
+    /// Leave `${VAR}` references in place for the tool to resolve at runtime
+    defer_shell_vars: bool,
+    /// Shell variables referenced since the last `take_shell_refs` call
+    shell_refs: Vec<(String, String)>,
+    /// Config variables referenced since the last `take_config_refs` call
+    config_refs: Vec<(String, String)>,
 }
 
 impl Expander {
@@ -80,9 +86,38 @@ impl Expander {
             env_section,
             shell_env,
             warnings: Vec::new(),
+            defer_shell_vars: false,
+            shell_refs: Vec::new(),
+            config_refs: Vec::new(),
         }
     }
 
+    /// Set whether `${VAR}` references are deferred to runtime
+    ///
+    /// When deferred, shell references are left verbatim in the expanded
+    /// value (including those reached through `{VAR}` config variables) so
+    /// a transformer can render them in the tool's own runtime syntax.
+    pub const fn set_defer_shell_vars(&mut self, defer: bool) {
+        self.defer_shell_vars = defer;
+    }
+
+    /// Take the shell variables referenced since the last call
+    ///
+    /// Returns `(name, value)` pairs for every defined shell variable that
+    /// was either substituted or deferred, so callers can track where
+    /// secret values flow.
+    pub fn take_shell_refs(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.shell_refs)
+    }
+
+    /// Take the config variables referenced since the last call
+    ///
+    /// Returns `(name, expanded value)` pairs for every defined `{VAR}`
+    /// reference, including nested ones.
+    pub fn take_config_refs(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.config_refs)
+    }
+
     /// Get collected warnings
     #[must_use]
     pub fn warnings(&self) -> &[String] {
@@ -117,37 +152,48 @@ impl Expander {
 
         let re = Regex::new(r"\$\{([^}]+)\}").unwrap();
         let mut result = value.to_string();
-
-        // Process all matches
-        #[allow(clippy::never_loop)]
-        loop {
-            let mut found_match = false;
-            let result_clone = result.clone();
-
-            for cap in re.captures_iter(&result_clone) {
-                let full_match = cap.get(0).unwrap().as_str();
-                let var_name = cap.get(1).unwrap().as_str();
-
-                if let Some(var_value) = self.shell_env.get(var_name) {
-                    result = result.replace(full_match, var_value);
+        let mut search_from = 0;
+
+        // Process matches one at a time, rescanning after each replacement so
+        // values that themselves contain references are expanded too
+        while let Some(cap) = re.captures_at(&result, search_from) {
+            let full_match = cap.get(0).unwrap();
+            let var_name = cap.get(1).unwrap().as_str().to_string();
+            let range = full_match.range();
+
+            if let Some(var_value) = self.shell_env.get(&var_name).cloned() {
+                self.record_shell_ref(&var_name, &var_value);
+                if self.defer_shell_vars {
+                    // Leave the reference for the tool to resolve at runtime
+                    search_from = range.end;
                 } else {
-                    // Undefined variable - replace with empty string and warn
-                    self.warnings
-                        .push(format!("Shell variable '{var_name}' is undefined"));
-                    result = result.replace(full_match, "");
+                    result.replace_range(range.clone(), &var_value);
+                    search_from = range.start;
+                }
+            } else {
+                // Undefined variable - warn, and replace with empty string
+                // unless it is resolved later at runtime
+                self.warnings
+                    .push(format!("Shell variable '{var_name}' is undefined"));
+                if self.defer_shell_vars {
+                    search_from = range.end;
+                } else {
+                    result.replace_range(range.clone(), "");
+                    search_from = range.start;
                 }
-                found_match = true;
-                break; // Restart from beginning after each replacement
-            }
-
-            if !found_match {
-                break;
             }
         }
 
         result
     }
 
+    /// Record a referenced shell variable once
+    fn record_shell_ref(&mut self, name: &str, value: &str) {
+        if !self.shell_refs.iter().any(|(n, _)| n == name) {
+            self.shell_refs.push((name.to_string(), value.to_string()));
+        }
+    }
+
     /// Expand config environment variables ({VAR} syntax) with circular
     /// reference detection
     ///
@@ -190,55 +236,52 @@ impl Expander {
         }
 
         use regex::Regex;
-        let re = Regex::new(r"\{([^}]+)\}").unwrap();
+        let re = Regex::new(r"\$?\{([^}]+)\}").unwrap();
         let mut result = value.to_string();
+        let mut search_from = 0;
+
+        // Process matches one at a time, rescanning after each replacement
+        while let Some(cap) = re.captures_at(&result, search_from) {
+            let full_match = cap.get(0).unwrap();
+            let var_name = cap.get(1).unwrap().as_str().to_string();
+            let range = full_match.range();
+
+            // Deferred shell references are not config variables
+            if full_match.as_str().starts_with('$') {
+                search_from = range.end;
+                continue;
+            }
 
-        // Process all matches
-        #[allow(clippy::never_loop)]
-        loop {
-            let mut found_match = false;
-            let result_clone = result.clone();
-
-            for cap in re.captures_iter(&result_clone) {
-                let full_match = cap.get(0).unwrap().as_str();
-                let var_name = cap.get(1).unwrap().as_str();
-
-                // Check for circular reference
-                if visited.contains(var_name) {
-                    return Err(ExpansionError::CircularReference {
-                        var_name: var_name.to_string(),
-                        depth,
-                    });
-                }
+            // Check for circular reference
+            if visited.contains(&var_name) {
+                return Err(ExpansionError::CircularReference { var_name, depth });
+            }
 
-                if let Some(var_value) = self.env_section.get(var_name).cloned() {
-                    // Mark as visited
-                    visited.insert(var_name.to_string());
+            if let Some(var_value) = self.env_section.get(&var_name).cloned() {
+                // Mark as visited
+                visited.insert(var_name.clone());
 
-                    // First expand any shell variables in this value
-                    let after_shell = self.expand_shell_vars(&var_value);
+                // First expand any shell variables in this value
+                let after_shell = self.expand_shell_vars(&var_value);
 
-                    // Then recursively expand config variables
-                    let expanded =
-                        self.expand_env_vars_recursive(&after_shell, depth + 1, visited)?;
+                // Then recursively expand config variables
+                let expanded = self.expand_env_vars_recursive(&after_shell, depth + 1, visited)?;
 
-                    // Unmark after expansion
-                    visited.remove(var_name);
+                // Unmark after expansion
+                visited.remove(&var_name);
 
-                    result = result.replace(full_match, &expanded);
-                } else {
-                    // Undefined variable - replace with empty string and warn
-                    self.warnings
-                        .push(format!("Config variable '{var_name}' is undefined"));
-                    result = result.replace(full_match, "");
+                if !self.config_refs.iter().any(|(n, _)| *n == var_name) {
+                    self.config_refs.push((var_name, expanded.clone()));
                 }
-                found_match = true;
-                break; // Restart from beginning after each replacement
-            }
 
-            if !found_match {
-                break;
+                result.replace_range(range.clone(), &expanded);
+            } else {
+                // Undefined variable - replace with empty string and warn
+                self.warnings
+                    .push(format!("Config variable '{var_name}' is undefined"));
+                result.replace_range(range.clone(), "");
             }
+            search_from = range.start;
         }
 
         Ok(result)
@@ -569,4 +612,64 @@ mod tests {
         let result = expander.expand("exe={PATH_PREFIX}/app").unwrap();
         assert_eq!(result, "exe=/opt/bin/app");
     }
+
+    #[test]
+    fn test_expand_deferred_keeps_shell_refs() {
+        let mut shell_env = HashMap::new();
+        shell_env.insert("TOKEN".to_string(), "secret".to_string());
+
+        let mut env_section = HashMap::new();
+        env_section.insert("GITHUB_TOKEN".to_string(), "${TOKEN}".to_string());
+        env_section.insert("HOST".to_string(), "example.com".to_string());
+
+        let mut expander = Expander::new(env_section, shell_env);
+        expander.set_defer_shell_vars(true);
+
+        // Config variables still expand, shell references stay verbatim
+        let result = expander.expand("{GITHUB_TOKEN}@{HOST}").unwrap();
+        assert_eq!(result, "${TOKEN}@example.com");
+        assert!(expander.warnings().is_empty());
+    }
+
+    #[test]
+    fn test_take_config_refs() {
+        let mut env_section = HashMap::new();
+        env_section.insert("API_TOKEN".to_string(), "{INNER}".to_string());
+        env_section.insert("INNER".to_string(), "tok_value".to_string());
+        let mut expander = Expander::new(env_section, HashMap::new());
+
+        let result = expander.expand("--token={API_TOKEN}").unwrap();
+        assert_eq!(result, "--token=tok_value");
+
+        let mut refs = expander.take_config_refs();
+        refs.sort();
+        assert_eq!(
+            refs,
+            vec![
+                ("API_TOKEN".to_string(), "tok_value".to_string()),
+                ("INNER".to_string(), "tok_value".to_string()),
+            ]
+        );
+        assert!(expander.take_config_refs().is_empty());
+    }
+
+    #[test]
+    fn test_take_shell_refs() {
+        let mut shell_env = HashMap::new();
+        shell_env.insert("TOKEN".to_string(), "secret".to_string());
+
+        let mut expander = Expander::new(HashMap::new(), shell_env);
+
+        expander.expand("${TOKEN}-${TOKEN}").unwrap();
+        assert_eq!(
+            expander.take_shell_refs(),
+            vec![("TOKEN".to_string(), "secret".to_string())]
+        );
+        assert!(expander.take_shell_refs().is_empty());
+
+        // Deferred references are tracked as well
+        expander.set_defer_shell_vars(true);
+        expander.expand("${TOKEN}").unwrap();
+        assert_eq!(expander.take_shell_refs().len(), 1);
+    }
 }
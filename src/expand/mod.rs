@@ -6,5 +6,7 @@
 //! - `{VAR}` - Variables from [env] section
 
 pub mod expander;
+pub mod secrets;
 
 pub use expander::{Expander, ExpansionResult};
+pub use secrets::{REDACTED, SecretRegistry, is_secret_name};
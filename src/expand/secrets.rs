@@ -0,0 +1,233 @@
+//! Tracking and redaction of secret values
+//!
+//! Secrets are recorded while the configuration is expanded and masked in
+//! everything printed to the console (INV-4, SEC-1 to SEC-3). Generated
+//! configuration files are the only place their values are written.
+
+use regex::{Captures, Regex};
+use std::{collections::BTreeMap, sync::LazyLock};
+
+/// Values shorter than this are too likely to collide with unrelated text
+pub const MIN_SECRET_LEN: usize = 4;
+
+/// Placeholder printed in place of a secret value
+pub const REDACTED: &str = "<redacted>";
+
+/// Variable and field names that conventionally hold credentials
+static SECRET_NAME: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)(TOKEN|SECRET|PASSWORD|PASSWD|CREDENTIAL|AUTHORIZATION|API[_-]?KEY|PRIVATE[_-]?KEY|(^|[_-])KEY$)",
+    )
+    .unwrap()
+});
+
+/// `"name": "value"` (JSON) or `name = "value"` (TOML) on a single line
+static ASSIGNMENT: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?m)(?P<key>"[^"\n]+"|[A-Za-z0-9_.-]+)(?P<sep>\s*[:=]\s*")(?P<value>[^"\n]*)""#)
+        .unwrap()
+});
+
+/// Values that are runtime references rather than secrets
+static RUNTIME_REFERENCE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(Bearer )?(\$\{(env:)?[^}]+\}|\{env:[^}]+\})$").unwrap());
+
+/// Whether a variable or field name conventionally holds a credential
+///
+/// Matches names such as `GITHUB_TOKEN`, `API_KEY`, `DB_PASSWORD` or
+/// `Authorization`, and header names such as `X-API-Key`.
+#[must_use]
+pub fn is_secret_name(name: &str) -> bool {
+    SECRET_NAME.is_match(name)
+}
+
+/// Registry of secret values discovered during expansion, keyed by the
+/// variable name they came from
+#[derive(Debug, Clone, Default)]
+pub struct SecretRegistry {
+    /// Variable name to secret value
+    secrets: BTreeMap<String, String>,
+    /// Print secrets verbatim instead of masking them
+    reveal: bool,
+}
+
+impl SecretRegistry {
+    /// Create an empty registry
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a secret value under the variable name it came from
+    ///
+    /// Values shorter than [`MIN_SECRET_LEN`] are ignored, as are values
+    /// already recorded under another name.
+    pub fn record(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        let value = value.into();
+        if value.len() >= MIN_SECRET_LEN && !self.secrets.values().any(|v| *v == value) {
+            self.secrets.insert(name.into(), value);
+        }
+    }
+
+    /// Set whether [`redact`](Self::redact) leaves secrets visible
+    ///
+    /// Backs the `--show-secrets` override for debugging.
+    pub const fn set_reveal(&mut self, reveal: bool) {
+        self.reveal = reveal;
+    }
+
+    /// Whether no secrets have been recorded
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+
+    /// Number of recorded secrets
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.secrets.len()
+    }
+
+    /// Names of the secrets whose values appear verbatim in `content`
+    #[must_use]
+    pub fn names_found_in(&self, content: &str) -> Vec<&str> {
+        self.secrets
+            .iter()
+            .filter(|(_, value)| content.contains(value.as_str()))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+
+    /// Mask secrets in text intended for the console
+    ///
+    /// Replaces every recorded secret value with [`REDACTED`], then masks the
+    /// value of any single-line `"KEY": "value"` or `KEY = "value"`
+    /// assignment whose key looks like a credential. The second pass catches
+    /// secrets that are not in the registry, such as stale values on disk.
+    /// Text is returned unchanged when reveal is set.
+    #[must_use]
+    pub fn redact(&self, text: &str) -> String {
+        if self.reveal {
+            return text.to_string();
+        }
+
+        let mut values: Vec<&str> = self.secrets.values().map(String::as_str).collect();
+        // Longest first so a secret containing another is masked whole
+        values.sort_by_key(|value| std::cmp::Reverse(value.len()));
+
+        let mut result = text.to_string();
+        for value in values {
+            result = result.replace(value, REDACTED);
+        }
+
+        redact_secret_fields(&result)
+    }
+}
+
+/// Mask values of single-line assignments whose key looks like a credential
+///
+/// Runtime references such as `${env:TOKEN}` are left visible since they
+/// carry no secret.
+#[must_use]
+pub fn redact_secret_fields(text: &str) -> String {
+    ASSIGNMENT
+        .replace_all(text, |caps: &Captures<'_>| {
+            let key = caps["key"].trim_matches('"');
+            let value = &caps["value"];
+            if is_secret_name(key)
+                && !value.is_empty()
+                && value != REDACTED
+                && !RUNTIME_REFERENCE.is_match(value)
+            {
+                format!("{}{}{REDACTED}\"", &caps["key"], &caps["sep"])
+            } else {
+                caps[0].to_string()
+            }
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_ignores_short_values() {
+        let mut registry = SecretRegistry::new();
+        registry.record("SHORT", "abc");
+        registry.record("TOKEN", "ghp_secret");
+
+        assert_eq!(registry.len(), 1);
+        assert!(!registry.is_empty());
+    }
+
+    #[test]
+    fn test_names_found_in() {
+        let mut registry = SecretRegistry::new();
+        registry.record("TOKEN", "ghp_secret");
+        registry.record("OTHER", "not-present");
+
+        let found = registry.names_found_in(r#"{"env": {"KEY": "ghp_secret"}}"#);
+        assert_eq!(found, vec!["TOKEN"]);
+        assert!(registry.names_found_in("${TOKEN}").is_empty());
+    }
+
+    #[test]
+    fn test_is_secret_name() {
+        assert!(is_secret_name("GITHUB_TOKEN"));
+        assert!(is_secret_name("API_KEY"));
+        assert!(is_secret_name("apiKey"));
+        assert!(is_secret_name("DB_PASSWORD"));
+        assert!(is_secret_name("Authorization"));
+        assert!(is_secret_name("X-API-Key"));
+        assert!(is_secret_name("bearer_token"));
+        assert!(!is_secret_name("LOG_LEVEL"));
+        assert!(!is_secret_name("KEYBOARD_LAYOUT"));
+    }
+
+    #[test]
+    fn test_redact_registered_values() {
+        let mut registry = SecretRegistry::new();
+        registry.record("TOKEN", "ghp_secret");
+
+        let redacted = registry.redact("+  --token=ghp_secret\n");
+        assert_eq!(redacted, "+  --token=<redacted>\n");
+    }
+
+    #[test]
+    fn test_redact_reveal() {
+        let mut registry = SecretRegistry::new();
+        registry.record("TOKEN", "ghp_secret");
+        registry.set_reveal(true);
+
+        let text = "API_KEY = \"ghp_secret\"";
+        assert_eq!(registry.redact(text), text);
+    }
+
+    #[test]
+    fn test_record_skips_known_values() {
+        let mut registry = SecretRegistry::new();
+        registry.record("GITHUB_TOKEN", "ghp_secret");
+        registry.record("github.env.TOKEN", "ghp_secret");
+
+        assert_eq!(registry.names_found_in("ghp_secret"), vec!["GITHUB_TOKEN"]);
+    }
+
+    #[test]
+    fn test_redact_secret_fields_json_and_toml() {
+        let text =
+            "-      \"GITHUB_TOKEN\": \"old_value\",\n+LOG_LEVEL = \"debug\"\n+API_KEY = \"abc\"\n";
+        let redacted = redact_secret_fields(text);
+
+        assert!(redacted.contains("\"GITHUB_TOKEN\": \"<redacted>\""));
+        assert!(redacted.contains("LOG_LEVEL = \"debug\""));
+        assert!(redacted.contains("API_KEY = \"<redacted>\""));
+        assert!(!redacted.contains("old_value"));
+    }
+
+    #[test]
+    #[allow(clippy::literal_string_with_formatting_args)]
+    fn test_redact_keeps_runtime_references() {
+        let text = "\"GITHUB_TOKEN\": \"${env:GITHUB_TOKEN}\"\nbearer_token = \"{env:API}\"\n";
+        assert_eq!(redact_secret_fields(text), text);
+    }
+}
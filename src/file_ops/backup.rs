@@ -1,15 +1,37 @@
 //! Backup creation and restoration
 
+use crate::file_ops::permissions::set_private_file_mode;
 use std::{
-    fs,
+    fs::{File, OpenOptions},
+    io,
     path::{Path, PathBuf},
 };
 
+/// Path of the backup for a file
+///
+/// Appends `.backup` to the file's extension, e.g. `mcp.json` becomes
+/// `mcp.json.backup`.
+#[must_use]
+pub fn backup_path(original_path: &Path) -> PathBuf {
+    original_path.with_extension(
+        format!(
+            "{}.backup",
+            original_path
+                .extension()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+        )
+        .trim_start_matches('.'),
+    )
+}
+
 #[allow(clippy::format_collect)]
 /// Create a backup of an existing file
 ///
 /// Creates a backup with `.backup` extension. If the original file doesn't
-/// exist, no backup is created and Ok(None) is returned.
+/// exist, no backup is created and Ok(None) is returned. The backup may hold
+/// secrets, so it is created with mode 0600 (and an existing backup is made
+/// private) before any content is written, whatever the original's mode.
 ///
 /// # Arguments
 ///
@@ -31,23 +53,29 @@ pub fn create_backup(original_path: &Path) -> Result<Option<PathBuf>, std::io::E
     }
 
     // Generate backup path
-    let backup_path = original_path.with_extension(
-        format!(
-            "{}.backup",
-            original_path
-                .extension()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-        )
-        .trim_start_matches('.'),
-    );
+    let backup_path = backup_path(original_path);
 
-    // Copy file to backup
-    fs::copy(original_path, &backup_path)?;
+    // Create the backup private, then copy the content into it
+    let mut source = File::open(original_path)?;
+    let mut backup = open_private(&backup_path)?;
+    set_private_file_mode(&backup_path)?;
+    io::copy(&mut source, &mut backup)?;
 
     Ok(Some(backup_path))
 }
 
+/// Open a file for writing, creating it with mode 0600 if it doesn't exist
+fn open_private(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(crate::file_ops::permissions::PRIVATE_FILE_MODE);
+    }
+    options.open(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,4 +159,26 @@ mod tests {
         let backup_content = fs::read_to_string(&backup).unwrap();
         assert_eq!(backup_content, original_content);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_backup_normalises_mode() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let original = temp_dir.path().join("mcp.json");
+        fs::write(&original, "{}").unwrap();
+        fs::set_permissions(&original, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let backup = create_backup(&original).unwrap().unwrap();
+        let mode = fs::metadata(&backup).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+        assert_eq!(backup, backup_path(&original));
+
+        // An existing backup with a looser mode is made private too
+        fs::set_permissions(&backup, fs::Permissions::from_mode(0o644)).unwrap();
+        create_backup(&original).unwrap();
+        let mode = fs::metadata(&backup).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode, 0o600);
+    }
 }
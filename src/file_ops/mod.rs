@@ -5,10 +5,14 @@
 
 pub mod backup;
 pub mod diff;
+pub mod permissions;
 pub mod state;
 pub mod writer;
 
-pub use backup::create_backup;
+pub use backup::{backup_path, create_backup};
 pub use diff::{generate_diff, generate_file_diff};
-pub use state::{GeneratedFile, StateFile, StateTracker, hash_file};
+pub use permissions::{PermissionIssue, check_private, make_private, sensitive_paths};
+pub use state::{
+    GeneratedFile, StateFile, StateTracker, default_launcher_dir, default_state_path, hash_file,
+};
 pub use writer::write_file_atomic;
@@ -0,0 +1,244 @@
+//! Permission and ownership checks for files that may contain secrets
+//!
+//! The unified config, state directory and backups can all hold tokens, so
+//! they must be private to the current user. On non-Unix platforms the checks
+//! report nothing.
+
+use crate::file_ops::{StateTracker, backup::backup_path, state::default_state_path};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Mode for private files: owner read/write only
+pub const PRIVATE_FILE_MODE: u32 = 0o600;
+
+/// A problem with a file's permissions or ownership
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PermissionIssue {
+    /// Group or other users have some access
+    TooOpen {
+        /// Current permission bits
+        mode: u32,
+    },
+    /// Owned by a different user
+    WrongOwner {
+        /// Owner's user ID
+        owner: u32,
+        /// Current user's ID
+        current: u32,
+    },
+}
+
+impl PermissionIssue {
+    /// Whether `make_private` can fix this issue
+    ///
+    /// Changing ownership needs privileges, so only modes are fixed.
+    #[must_use]
+    pub const fn is_fixable(&self) -> bool {
+        matches!(self, Self::TooOpen { .. })
+    }
+}
+
+impl fmt::Display for PermissionIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TooOpen { mode } => {
+                write!(f, "mode {mode:04o} allows group/world access")
+            }
+            Self::WrongOwner { owner, current } => {
+                write!(
+                    f,
+                    "owned by uid {owner}, not the current user (uid {current})"
+                )
+            }
+        }
+    }
+}
+
+/// Check that a file or directory is private to the current user
+///
+/// # Arguments
+///
+/// * `path` - File or directory to check
+///
+/// # Returns
+///
+/// * `Ok(Vec<PermissionIssue>)` - Problems found (empty if private)
+/// * `Err(io::Error)` - Path cannot be inspected
+///
+/// # Errors
+///
+/// Returns error if the path's metadata cannot be read
+#[cfg(unix)]
+pub fn check_private(path: &Path) -> io::Result<Vec<PermissionIssue>> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)?;
+    let mut issues = Vec::new();
+
+    let mode = metadata.mode() & 0o7777;
+    if mode & 0o077 != 0 {
+        issues.push(PermissionIssue::TooOpen { mode });
+    }
+
+    let current = rustix::process::getuid().as_raw();
+    if metadata.uid() != current {
+        issues.push(PermissionIssue::WrongOwner {
+            owner: metadata.uid(),
+            current,
+        });
+    }
+
+    Ok(issues)
+}
+
+/// Check that a file or directory is private to the current user
+///
+/// # Errors
+///
+/// Returns error if the path's metadata cannot be read
+#[cfg(not(unix))]
+pub fn check_private(path: &Path) -> io::Result<Vec<PermissionIssue>> {
+    fs::metadata(path)?;
+    Ok(Vec::new())
+}
+
+/// Remove group and world access from a file or directory
+///
+/// Owner bits are kept, so directories stay traversable.
+///
+/// # Errors
+///
+/// Returns error if permissions cannot be read or set
+#[cfg(unix)]
+pub fn make_private(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mode = fs::metadata(path)?.permissions().mode();
+    fs::set_permissions(path, fs::Permissions::from_mode(mode & 0o700))
+}
+
+/// Remove group and world access from a file or directory
+///
+/// # Errors
+///
+/// Returns error if the path's metadata cannot be read
+#[cfg(not(unix))]
+pub fn make_private(path: &Path) -> io::Result<()> {
+    fs::metadata(path).map(|_| ())
+}
+
+/// Set a file's mode to [`PRIVATE_FILE_MODE`]
+///
+/// # Errors
+///
+/// Returns error if permissions cannot be set
+#[cfg(unix)]
+pub fn set_private_file_mode(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(PRIVATE_FILE_MODE))
+}
+
+/// Set a file's mode to [`PRIVATE_FILE_MODE`]
+///
+/// Windows has a different permission model, so this does nothing.
+///
+/// # Errors
+///
+/// Never fails on this platform
+#[cfg(not(unix))]
+pub const fn set_private_file_mode(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+/// Existing files and directories that may contain secrets
+///
+/// The unified config, the state directory and state file, and the backups
+/// of every generated file recorded in the state.
+#[must_use]
+pub fn sensitive_paths(config_path: &Path) -> Vec<PathBuf> {
+    let state_path = default_state_path();
+    let mut paths = vec![config_path.to_path_buf()];
+
+    if let Some(state_dir) = state_path.parent() {
+        paths.push(state_dir.to_path_buf());
+    }
+    paths.push(state_path.clone());
+
+    if let Ok(tracker) = StateTracker::load(&state_path) {
+        paths.extend(
+            tracker
+                .generated_files()
+                .iter()
+                .map(|file| backup_path(&file.path)),
+        );
+    }
+
+    paths.retain(|path| path.exists());
+    paths
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    fn mode_of(path: &Path) -> u32 {
+        fs::metadata(path).unwrap().permissions().mode() & 0o777
+    }
+
+    #[test]
+    fn test_check_private_ok() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("config.toml");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o600)).unwrap();
+
+        assert!(check_private(&file).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_check_private_too_open() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("config.toml");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let issues = check_private(&file).unwrap();
+        assert_eq!(issues, vec![PermissionIssue::TooOpen { mode: 0o644 }]);
+        assert!(issues[0].is_fixable());
+        assert_eq!(issues[0].to_string(), "mode 0644 allows group/world access");
+    }
+
+    #[test]
+    fn test_make_private() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("config.toml");
+        fs::write(&file, "").unwrap();
+        fs::set_permissions(&file, fs::Permissions::from_mode(0o664)).unwrap();
+
+        make_private(&file).unwrap();
+        assert_eq!(mode_of(&file), 0o600);
+
+        let dir = temp_dir.path().join("state");
+        fs::create_dir(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        make_private(&dir).unwrap();
+        assert_eq!(mode_of(&dir), 0o700);
+    }
+
+    #[test]
+    fn test_wrong_owner_not_fixable() {
+        let issue = PermissionIssue::WrongOwner {
+            owner: 0,
+            current: 1000,
+        };
+        assert!(!issue.is_fixable());
+        assert!(issue.to_string().contains("uid 0"));
+    }
+}
@@ -1,5 +1,6 @@
 //! State tracking with SHA-256 hashing
 
+use crate::config::command::ResolvedCommand;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -20,6 +21,19 @@ pub struct StateFile {
 
     /// List of generated files
     pub generated_files: Vec<GeneratedFile>,
+
+    /// Commands pinned to absolute paths by the last compile
+    #[serde(default)]
+    pub resolved_commands: Vec<ResolvedCommand>,
+
+    /// Launcher scripts written by previous compiles
+    #[serde(default)]
+    pub launchers: Vec<GeneratedFile>,
+
+    /// Permission rules written to Claude Code's settings by the last
+    /// compile
+    #[serde(default)]
+    pub permissions: Vec<String>,
 }
 
 /// Record of a generated configuration file
@@ -85,6 +99,9 @@ impl StateTracker {
             version: "1.0".to_string(),
             last_compile: Utc::now(),
             generated_files: Vec::new(),
+            resolved_commands: Vec::new(),
+            launchers: Vec::new(),
+            permissions: Vec::new(),
         }
     }
 
@@ -111,6 +128,69 @@ impl StateTracker {
         self.state.last_compile = Utc::now();
     }
 
+    /// Time of the last compile
+    #[must_use]
+    pub const fn last_compile(&self) -> DateTime<Utc> {
+        self.state.last_compile
+    }
+
+    /// Files recorded by previous compiles
+    #[must_use]
+    pub fn generated_files(&self) -> &[GeneratedFile] {
+        &self.state.generated_files
+    }
+
+    /// Record the commands pinned by a compile, replacing earlier ones
+    pub fn set_resolved_commands(&mut self, resolved: Vec<ResolvedCommand>) {
+        self.state.resolved_commands = resolved;
+    }
+
+    /// Commands pinned to absolute paths by the last compile
+    #[must_use]
+    pub fn resolved_commands(&self) -> &[ResolvedCommand] {
+        &self.state.resolved_commands
+    }
+
+    /// Record the launchers written for a tool, replacing its earlier ones
+    ///
+    /// # Returns
+    ///
+    /// Launchers previously recorded for the tool that were not written
+    /// again, whose files can be removed
+    pub fn replace_launchers(
+        &mut self,
+        tool: &str,
+        launchers: Vec<GeneratedFile>,
+    ) -> Vec<GeneratedFile> {
+        let (previous, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.state.launchers)
+            .into_iter()
+            .partition(|launcher| launcher.tool == tool);
+        let stale = previous
+            .into_iter()
+            .filter(|old| !launchers.iter().any(|new| new.path == old.path))
+            .collect();
+        self.state.launchers = kept;
+        self.state.launchers.extend(launchers);
+        stale
+    }
+
+    /// Launcher scripts written by previous compiles
+    #[must_use]
+    pub fn launchers(&self) -> &[GeneratedFile] {
+        &self.state.launchers
+    }
+
+    /// Record the permission rules written to Claude Code's settings
+    pub fn set_permissions(&mut self, rules: Vec<String>) {
+        self.state.permissions = rules;
+    }
+
+    /// Permission rules the last compile wrote to Claude Code's settings
+    #[must_use]
+    pub fn permissions(&self) -> &[String] {
+        &self.state.permissions
+    }
+
     /// Get the recorded hash for a file
     ///
     /// # Arguments
@@ -156,6 +236,26 @@ impl StateTracker {
     }
 }
 
+/// Default location of the state file
+///
+/// `<config dir>/multi-agent-config/state/generated.json`
+#[must_use]
+pub fn default_state_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("multi-agent-config")
+        .join("state")
+        .join("generated.json")
+}
+
+/// Default directory for generated launcher scripts
+///
+/// `<config dir>/multi-agent-config/state/launchers`
+#[must_use]
+pub fn default_launcher_dir() -> PathBuf {
+    default_state_path().with_file_name("launchers")
+}
+
 #[allow(clippy::format_collect)]
 /// Compute SHA-256 hash of a file
 ///
@@ -254,6 +354,58 @@ mod tests {
         assert_eq!(tracker2.state.generated_files[0].tool, "cursor");
     }
 
+    #[test]
+    fn test_state_tracker_resolved_commands() {
+        let temp_dir = TempDir::new().unwrap();
+        let state_path = temp_dir.path().join("state.json");
+
+        // State files written before resolved commands existed still load
+        fs::write(
+            &state_path,
+            r#"{"version":"1.0","last_compile":"2025-10-12T00:00:00Z","generated_files":[]}"#,
+        )
+        .unwrap();
+        let mut tracker = StateTracker::load(&state_path).unwrap();
+        assert!(tracker.resolved_commands().is_empty());
+
+        let resolved = ResolvedCommand {
+            server: "github".to_string(),
+            command: "npx".to_string(),
+            path: PathBuf::from("/usr/bin/npx"),
+        };
+        tracker.set_resolved_commands(vec![resolved.clone()]);
+        tracker.save().unwrap();
+
+        let tracker = StateTracker::load(&state_path).unwrap();
+        assert_eq!(tracker.resolved_commands(), [resolved]);
+        assert!(tracker.launchers().is_empty());
+    }
+
+    #[test]
+    fn test_state_tracker_replace_launchers() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut tracker = StateTracker::load(&temp_dir.path().join("state.json")).unwrap();
+        let launcher = |tool: &str, name: &str| GeneratedFile {
+            tool: tool.to_string(),
+            path: PathBuf::from(format!("/launchers/{tool}/{name}.sh")),
+            timestamp: Utc::now(),
+            hash: "sha256:test".to_string(),
+        };
+
+        let stale = tracker.replace_launchers(
+            "codex",
+            vec![launcher("codex", "github"), launcher("codex", "files")],
+        );
+        assert!(stale.is_empty());
+        tracker.replace_launchers("cursor", vec![launcher("cursor", "github")]);
+
+        // Files is no longer launched for codex; cursor is untouched
+        let stale = tracker.replace_launchers("codex", vec![launcher("codex", "github")]);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].path, PathBuf::from("/launchers/codex/files.sh"));
+        assert_eq!(tracker.launchers().len(), 2);
+    }
+
     #[test]
     fn test_hash_file() {
         let temp_dir = TempDir::new().unwrap();
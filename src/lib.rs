@@ -4,10 +4,13 @@
 //! configuration into tool-specific MCP (Model Context Protocol) server
 //! configurations.
 
+pub mod catalog;
 pub mod config;
+pub mod diagnostics;
 pub mod error;
 pub mod expand;
 pub mod file_ops;
+pub mod secrets;
 pub mod transform;
 
 pub use config::{
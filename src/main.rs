@@ -3,13 +3,18 @@
 //! Command-line interface for managing AI coding tool configurations.
 
 use clap::{Parser, Subcommand};
-use std::path::PathBuf;
+use multi_agent_config::{config::parse_and_expand_config_with_secrets, error::MultiAgentError};
+use std::path::{Path, PathBuf};
 use workhelix_cli_common::{DoctorCheck, DoctorChecks, RepoInfo};
 
 mod cli;
 mod doctor;
 
-use cli::commands::{compile_command, diff_command, init_command, validate_command};
+use cli::commands::{
+    AddArgs, DiagnosticFormat, SecretsAction, add_command, catalog_command, compile_command,
+    diff_command, enable_command, init_command, lock_command, remove_command, secrets_command,
+    set_command, status_command, validate_command,
+};
 
 /// Application version from Cargo.toml
 const VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -32,6 +37,10 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Print secret values instead of masking them as <redacted>
+    #[arg(long, global = true)]
+    show_secrets: bool,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -47,7 +56,20 @@ enum Commands {
     },
 
     /// Validate configuration without writing
-    Validate,
+    Validate {
+        /// Print diagnostics as text snippets or as JSON on stdout
+        #[arg(long, value_enum, default_value_t)]
+        format: DiagnosticFormat,
+
+        /// Fail on warnings as well as errors
+        #[arg(long)]
+        deny_warnings: bool,
+
+        /// PATH to resolve server commands against, e.g. the minimal PATH a
+        /// GUI app launches with
+        #[arg(long, value_name = "PATH")]
+        search_path: Option<String>,
+    },
 
     /// Compile and write tool configurations
     Compile {
@@ -67,6 +89,55 @@ enum Commands {
         tool: Vec<String>,
     },
 
+    /// Show generated files and resolved commands from the last compile
+    Status,
+
+    /// Add a server to the config
+    Add(AddArgs),
+
+    /// Remove a server from the config
+    Remove {
+        /// Server name
+        name: String,
+    },
+
+    /// Enable a server
+    Enable {
+        /// Server name
+        name: String,
+    },
+
+    /// Disable a server without removing it
+    Disable {
+        /// Server name
+        name: String,
+    },
+
+    /// Set a field of a server, e.g. `set github.targets cursor,codex`
+    Set {
+        /// Field as <server>.<field>
+        field: String,
+
+        /// Value; lists such as targets may be comma-separated
+        value: String,
+    },
+
+    /// List the servers in the catalog
+    Catalog,
+
+    /// Record package versions in the lock file next to the config
+    Lock {
+        /// Look up the latest version of packages not pinned in the config
+        #[arg(short, long)]
+        update: bool,
+    },
+
+    /// Manage the encrypted secrets file next to the config
+    Secrets {
+        #[command(subcommand)]
+        action: SecretsAction,
+    },
+
     /// Show version information
     Version,
 
@@ -80,7 +151,11 @@ enum Commands {
     },
 
     /// Run health check and diagnostics
-    Doctor,
+    Doctor {
+        /// Remove group/world access from the config, state and backups
+        #[arg(long)]
+        fix: bool,
+    },
 
     /// Update to latest version
     Update {
@@ -111,6 +186,8 @@ fn main() {
 
     // Get config path, using default if not provided
     let config_path = cli.config.unwrap_or_else(default_config_path);
+    let show_secrets = cli.show_secrets;
+    let report = |result| exit_code(result, &config_path, show_secrets);
 
     let exit_code = match cli.command {
         Commands::Version => {
@@ -121,42 +198,53 @@ fn main() {
             license_command();
             0
         }
-        Commands::Init { force } => match init_command(&config_path, force) {
-            Ok(()) => 0,
-            Err(e) => {
-                eprintln!("{}", e.format_with_suggestion());
-                e.exit_code()
-            }
-        },
-        Commands::Validate => match validate_command(&config_path, cli.verbose) {
-            Ok(()) => 0,
-            Err(e) => {
-                eprintln!("{}", e.format_with_suggestion());
-                e.exit_code()
-            }
-        },
-        Commands::Compile { tool, dry_run } => {
-            match compile_command(&config_path, &tool, dry_run, cli.verbose) {
-                Ok(()) => 0,
-                Err(e) => {
-                    eprintln!("{}", e.format_with_suggestion());
-                    e.exit_code()
-                }
-            }
+        Commands::Init { force } => report(init_command(&config_path, force)),
+        Commands::Validate {
+            format,
+            deny_warnings,
+            search_path,
+        } => report(validate_command(
+            &config_path,
+            cli.verbose,
+            cli.show_secrets,
+            format,
+            deny_warnings,
+            search_path.as_deref(),
+        )),
+        Commands::Compile { tool, dry_run } => report(compile_command(
+            &config_path,
+            &tool,
+            dry_run,
+            cli.verbose,
+            cli.show_secrets,
+        )),
+        Commands::Diff { tool } => report(diff_command(
+            &config_path,
+            &tool,
+            cli.verbose,
+            cli.show_secrets,
+        )),
+        Commands::Status => report(status_command(cli.verbose)),
+        Commands::Add(add) => report(add_command(&config_path, &add, cli.verbose)),
+        Commands::Remove { name } => report(remove_command(&config_path, &name, cli.verbose)),
+        Commands::Enable { name } => report(enable_command(&config_path, &name, true, cli.verbose)),
+        Commands::Disable { name } => {
+            report(enable_command(&config_path, &name, false, cli.verbose))
         }
-        Commands::Diff { tool } => match diff_command(&config_path, &tool, cli.verbose) {
-            Ok(()) => 0,
-            Err(e) => {
-                eprintln!("{}", e.format_with_suggestion());
-                e.exit_code()
-            }
-        },
+        Commands::Set { field, value } => {
+            report(set_command(&config_path, &field, &value, cli.verbose))
+        }
+        Commands::Catalog => report(catalog_command(&config_path, cli.verbose)),
+        Commands::Lock { update } => report(lock_command(&config_path, update, cli.verbose)),
+        Commands::Secrets { action } => report(secrets_command(&config_path, action, cli.verbose)),
         Commands::Completions { shell } => {
             workhelix_cli_common::completions::generate_completions::<Cli>(shell);
             0
         }
-        Commands::Doctor => {
-            struct MultiAgentConfigTool;
+        Commands::Doctor { fix } => {
+            struct MultiAgentConfigTool {
+                fix: bool,
+            }
 
             impl DoctorChecks for MultiAgentConfigTool {
                 fn repo_info() -> RepoInfo {
@@ -168,11 +256,11 @@ fn main() {
                 }
 
                 fn tool_checks(&self) -> Vec<DoctorCheck> {
-                    doctor::tool_specific_checks(&default_config_path())
+                    doctor::tool_specific_checks(&default_config_path(), self.fix)
                 }
             }
 
-            let tool = MultiAgentConfigTool;
+            let tool = MultiAgentConfigTool { fix };
             workhelix_cli_common::doctor::run_doctor(&tool)
         }
         Commands::Update {
@@ -194,6 +282,24 @@ fn main() {
     std::process::exit(exit_code);
 }
 
+/// Print a command's error, if any, and return the process exit code
+///
+/// The error is redacted with the config's secrets first, since messages
+/// can quote expanded values.
+fn exit_code(result: Result<(), MultiAgentError>, config_path: &Path, show_secrets: bool) -> i32 {
+    result.map_or_else(
+        |e| {
+            let mut secrets = parse_and_expand_config_with_secrets(config_path)
+                .map(|(_, secrets)| secrets)
+                .unwrap_or_default();
+            secrets.set_reveal(show_secrets);
+            eprintln!("{}", secrets.redact(&e.format_with_suggestion()));
+            e.exit_code()
+        },
+        |()| 0,
+    )
+}
+
 /// Print version information
 fn version_command() {
     println!("multi-agent-config {VERSION}");
@@ -0,0 +1,318 @@
+//! Encryption of the secrets file
+//!
+//! The file is a small TOML envelope around base64 ciphertext, so it diffs
+//! and merges like any other text file:
+//!
+//! ```toml
+//! version = 1
+//! kdf = "pbkdf2-sha256"
+//! iterations = 600000
+//! salt = "..."
+//! nonce = "..."
+//! ciphertext = "..."
+//! ```
+
+use crate::secrets::SecretsError;
+use base64::{Engine, engine::general_purpose::STANDARD};
+use ring::{
+    aead::{self, Aad, CHACHA20_POLY1305, LessSafeKey, Nonce, UnboundKey},
+    pbkdf2,
+    rand::{SecureRandom, SystemRandom},
+};
+use serde::{Deserialize, Serialize};
+use std::{num::NonZeroU32, path::Path};
+
+/// Length of an encryption key in bytes
+pub const KEY_LEN: usize = 32;
+
+/// Envelope format version
+pub const ENVELOPE_VERSION: u32 = 1;
+
+/// PBKDF2 iterations for passphrase-derived keys
+pub const PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Length of the PBKDF2 salt in bytes
+const SALT_LEN: usize = 16;
+
+/// How the encryption key is obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kdf {
+    /// Random key read from a key file
+    KeyFile,
+    /// Key derived from a passphrase with PBKDF2-HMAC-SHA256
+    Pbkdf2Sha256,
+}
+
+/// Key material before derivation
+pub enum KeyMaterial {
+    /// Random key from a key file
+    Raw([u8; KEY_LEN]),
+    /// Passphrase, stretched with PBKDF2
+    Passphrase(String),
+}
+
+impl KeyMaterial {
+    /// Derivation used for this material
+    #[must_use]
+    pub const fn kdf(&self) -> Kdf {
+        match self {
+            Self::Raw(_) => Kdf::KeyFile,
+            Self::Passphrase(_) => Kdf::Pbkdf2Sha256,
+        }
+    }
+
+    /// Derive the encryption key for a salt and iteration count
+    fn derive(&self, salt: &[u8], iterations: u32) -> [u8; KEY_LEN] {
+        match self {
+            Self::Raw(key) => *key,
+            Self::Passphrase(passphrase) => {
+                let mut key = [0u8; KEY_LEN];
+                let iterations = NonZeroU32::new(iterations).unwrap_or(NonZeroU32::MIN);
+                pbkdf2::derive(
+                    pbkdf2::PBKDF2_HMAC_SHA256,
+                    iterations,
+                    salt,
+                    passphrase.as_bytes(),
+                    &mut key,
+                );
+                key
+            }
+        }
+    }
+}
+
+/// Encrypted secrets file contents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Envelope {
+    /// Format version
+    pub version: u32,
+    /// Key derivation
+    pub kdf: Kdf,
+    /// PBKDF2 iterations (passphrase keys only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iterations: Option<u32>,
+    /// Base64 PBKDF2 salt (passphrase keys only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub salt: Option<String>,
+    /// Base64 nonce
+    pub nonce: String,
+    /// Base64 ciphertext with authentication tag
+    pub ciphertext: String,
+}
+
+impl Envelope {
+    /// Additional authenticated data binding the header to the ciphertext
+    fn aad(&self) -> String {
+        format!(
+            "multi-agent-config secrets v{} {:?} {}",
+            self.version,
+            self.kdf,
+            self.iterations.unwrap_or(0)
+        )
+    }
+
+    /// Encrypt plaintext under the given key material
+    ///
+    /// A fresh nonce (and salt, for passphrases) is generated on every call.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the system random number generator fails
+    pub fn seal(plaintext: &str, key: &KeyMaterial) -> Result<Self, SecretsError> {
+        Self::seal_with_iterations(plaintext, key, PBKDF2_ITERATIONS)
+    }
+
+    /// Encrypt with an explicit PBKDF2 iteration count
+    fn seal_with_iterations(
+        plaintext: &str,
+        key: &KeyMaterial,
+        iterations: u32,
+    ) -> Result<Self, SecretsError> {
+        let rng = SystemRandom::new();
+
+        let (salt, iterations) = match key {
+            KeyMaterial::Raw(_) => (Vec::new(), None),
+            KeyMaterial::Passphrase(_) => {
+                let mut salt = vec![0u8; SALT_LEN];
+                rng.fill(&mut salt).map_err(|_| SecretsError::Random)?;
+                (salt, Some(iterations))
+            }
+        };
+
+        let mut nonce = [0u8; aead::NONCE_LEN];
+        rng.fill(&mut nonce).map_err(|_| SecretsError::Random)?;
+
+        let mut envelope = Self {
+            version: ENVELOPE_VERSION,
+            kdf: key.kdf(),
+            iterations,
+            salt: iterations.map(|_| STANDARD.encode(&salt)),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: String::new(),
+        };
+
+        let sealing_key = aead_key(&key.derive(&salt, iterations.unwrap_or(0)));
+        let mut in_out = plaintext.as_bytes().to_vec();
+        sealing_key
+            .seal_in_place_append_tag(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(envelope.aad().as_bytes()),
+                &mut in_out,
+            )
+            .map_err(|_| SecretsError::Encrypt)?;
+        envelope.ciphertext = STANDARD.encode(in_out);
+
+        Ok(envelope)
+    }
+
+    /// Decrypt the envelope
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - Key material matching the envelope's `kdf`
+    /// * `path` - File the envelope came from, for error messages
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the envelope is malformed, or the key is wrong or
+    /// the file was modified
+    pub fn open(&self, key: &KeyMaterial, path: &Path) -> Result<String, SecretsError> {
+        if self.version != ENVELOPE_VERSION {
+            return Err(SecretsError::format(
+                path,
+                format!("unsupported version {}", self.version),
+            ));
+        }
+        if key.kdf() != self.kdf {
+            return Err(SecretsError::Decrypt(path.to_path_buf()));
+        }
+
+        let decode = |field: &str, value: &str| {
+            STANDARD
+                .decode(value)
+                .map_err(|e| SecretsError::format(path, format!("invalid {field}: {e}")))
+        };
+
+        let salt = match &self.salt {
+            Some(salt) => decode("salt", salt)?,
+            None => Vec::new(),
+        };
+        let nonce: [u8; aead::NONCE_LEN] = decode("nonce", &self.nonce)?
+            .try_into()
+            .map_err(|_| SecretsError::format(path, "invalid nonce length"))?;
+        let mut in_out = decode("ciphertext", &self.ciphertext)?;
+
+        let opening_key = aead_key(&key.derive(&salt, self.iterations.unwrap_or(0)));
+        let plaintext = opening_key
+            .open_in_place(
+                Nonce::assume_unique_for_key(nonce),
+                Aad::from(self.aad().as_bytes()),
+                &mut in_out,
+            )
+            .map_err(|_| SecretsError::Decrypt(path.to_path_buf()))?;
+
+        String::from_utf8(plaintext.to_vec())
+            .map_err(|_| SecretsError::format(path, "decrypted contents are not UTF-8"))
+    }
+}
+
+/// Build a ChaCha20-Poly1305 key
+fn aead_key(key: &[u8; KEY_LEN]) -> LessSafeKey {
+    // Key length always matches the algorithm
+    LessSafeKey::new(UnboundKey::new(&CHACHA20_POLY1305, key).unwrap())
+}
+
+/// Generate a random key
+///
+/// # Errors
+///
+/// Returns error if the system random number generator fails
+pub fn generate_key() -> Result<[u8; KEY_LEN], SecretsError> {
+    let mut key = [0u8; KEY_LEN];
+    SystemRandom::new()
+        .fill(&mut key)
+        .map_err(|_| SecretsError::Random)?;
+    Ok(key)
+}
+
+/// Encode a key for storage in a key file
+#[must_use]
+pub fn encode_key(key: &[u8; KEY_LEN]) -> String {
+    format!("{}\n", STANDARD.encode(key))
+}
+
+/// Decode a key file's contents
+///
+/// # Errors
+///
+/// Returns error if the contents are not a base64-encoded 32-byte key
+pub fn decode_key(contents: &str, path: &Path) -> Result<[u8; KEY_LEN], SecretsError> {
+    STANDARD
+        .decode(contents.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| SecretsError::InvalidKey(path.to_path_buf()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_open_key_file() {
+        let key = KeyMaterial::Raw(generate_key().unwrap());
+        let envelope = Envelope::seal("TOKEN = \"value\"\n", &key).unwrap();
+
+        assert_eq!(envelope.kdf, Kdf::KeyFile);
+        assert!(envelope.salt.is_none());
+        assert!(!envelope.ciphertext.contains("value"));
+        assert_eq!(
+            envelope.open(&key, Path::new("s.enc")).unwrap(),
+            "TOKEN = \"value\"\n"
+        );
+    }
+
+    #[test]
+    fn test_seal_open_passphrase() {
+        let key = KeyMaterial::Passphrase("correct horse".to_string());
+        let envelope = Envelope::seal_with_iterations("A = \"b\"", &key, 1000).unwrap();
+
+        assert_eq!(envelope.kdf, Kdf::Pbkdf2Sha256);
+        assert_eq!(envelope.iterations, Some(1000));
+        assert_eq!(
+            envelope.open(&key, Path::new("s.enc")).unwrap(),
+            "A = \"b\""
+        );
+
+        let wrong = KeyMaterial::Passphrase("wrong".to_string());
+        assert!(matches!(
+            envelope.open(&wrong, Path::new("s.enc")),
+            Err(SecretsError::Decrypt(_))
+        ));
+    }
+
+    #[test]
+    fn test_open_detects_tampering() {
+        let key = KeyMaterial::Raw(generate_key().unwrap());
+        let mut envelope = Envelope::seal("A = \"b\"", &key).unwrap();
+
+        // Header changes are authenticated too
+        envelope.kdf = Kdf::Pbkdf2Sha256;
+        envelope.iterations = Some(1);
+        let passphrase = KeyMaterial::Passphrase(String::new());
+        assert!(envelope.open(&passphrase, Path::new("s.enc")).is_err());
+        assert!(envelope.open(&key, Path::new("s.enc")).is_err());
+    }
+
+    #[test]
+    fn test_key_encoding_round_trip() {
+        let key = generate_key().unwrap();
+        let encoded = encode_key(&key);
+        assert_eq!(decode_key(&encoded, Path::new("k")).unwrap(), key);
+        assert!(matches!(
+            decode_key("not a key", Path::new("k")),
+            Err(SecretsError::InvalidKey(_))
+        ));
+    }
+}
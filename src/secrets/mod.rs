@@ -0,0 +1,96 @@
+//! Encrypted secrets file
+//!
+//! A `secrets.toml.enc` next to `config.toml` holds `NAME = "value"` pairs
+//! encrypted with ChaCha20-Poly1305, so it can be committed alongside the
+//! config. Decrypted values are merged into the `[env]` section at compile
+//! time. The key is either a local key file or a passphrase taken from an
+//! environment variable:
+//!
+//! - `MULTI_AGENT_CONFIG_KEY_FILE` - Key file path, defaulting to `secrets.key`
+//!   in the per-user data directory
+//! - `MULTI_AGENT_CONFIG_PASSPHRASE` - Passphrase; new files use it instead of
+//!   the key file when set
+//! - `MULTI_AGENT_CONFIG_NEW_PASSPHRASE` - Replacement passphrase used when
+//!   rotating
+
+pub mod crypto;
+pub mod store;
+
+pub use crypto::{Envelope, Kdf, KeyMaterial};
+pub use store::{SECRETS_FILE_NAME, SecretsFile, secrets_file_path};
+
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// Environment variable naming the key file
+pub const KEY_FILE_ENV: &str = "MULTI_AGENT_CONFIG_KEY_FILE";
+
+/// Environment variable holding the passphrase
+pub const PASSPHRASE_ENV: &str = "MULTI_AGENT_CONFIG_PASSPHRASE";
+
+/// Environment variable holding the replacement passphrase during rotation
+pub const NEW_PASSPHRASE_ENV: &str = "MULTI_AGENT_CONFIG_NEW_PASSPHRASE";
+
+/// Errors reading, decrypting or writing the secrets file
+#[derive(Debug, Error)]
+pub enum SecretsError {
+    /// Secrets file does not exist
+    #[error("No secrets file at {0}; create it with 'multi-agent-config secrets set'")]
+    NotFound(PathBuf),
+
+    /// No key is available to decrypt or encrypt the file
+    #[error("No key available for {file}: {hint}")]
+    KeyMissing {
+        /// Secrets file that needs the key
+        file: PathBuf,
+        /// How to provide the key
+        hint: String,
+    },
+
+    /// Key file exists but is not a valid key
+    #[error("Invalid key file {0}: expected a base64-encoded 32-byte key")]
+    InvalidKey(PathBuf),
+
+    /// Refusing to overwrite an existing key file
+    #[error("Key file already exists: {0}")]
+    KeyFileExists(PathBuf),
+
+    /// Authentication failed: wrong key or modified file
+    #[error("Cannot decrypt {0}: wrong key, or the file has been modified")]
+    Decrypt(PathBuf),
+
+    /// File is not a valid secrets file
+    #[error("Invalid secrets file {file}: {message}")]
+    Format {
+        /// Offending file
+        file: PathBuf,
+        /// What is wrong with it
+        message: String,
+    },
+
+    /// Requested secret is not in the file
+    #[error("Secret '{0}' not found")]
+    UnknownSecret(String),
+
+    /// System random number generator failed
+    #[error("Random number generation failed")]
+    Random,
+
+    /// Encryption failed
+    #[error("Encryption failed")]
+    Encrypt,
+
+    /// I/O error
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl SecretsError {
+    /// Create a format error
+    pub fn format(file: impl Into<PathBuf>, message: impl Into<String>) -> Self {
+        Self::Format {
+            file: file.into(),
+            message: message.into(),
+        }
+    }
+}
@@ -0,0 +1,377 @@
+//! Loading, editing and saving the secrets file
+
+use crate::{
+    file_ops::{create_backup, write_file_atomic},
+    secrets::{
+        KEY_FILE_ENV, NEW_PASSPHRASE_ENV, PASSPHRASE_ENV, SecretsError,
+        crypto::{Envelope, Kdf, KeyMaterial, decode_key, encode_key, generate_key},
+    },
+};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+/// File name of the secrets file, next to the unified config
+pub const SECRETS_FILE_NAME: &str = "secrets.toml.enc";
+
+/// Path of the secrets file for a config file
+#[must_use]
+pub fn secrets_file_path(config_path: &Path) -> PathBuf {
+    config_path.with_file_name(SECRETS_FILE_NAME)
+}
+
+/// Path of the key file
+///
+/// `MULTI_AGENT_CONFIG_KEY_FILE` if set, otherwise
+/// `<data dir>/multi-agent-config/secrets.key`. The default lives outside the
+/// config directory so it is not committed with the config by accident.
+#[must_use]
+pub fn key_file_path() -> PathBuf {
+    env::var_os(KEY_FILE_ENV).map_or_else(
+        || {
+            dirs::data_dir()
+                .unwrap_or_else(|| PathBuf::from("."))
+                .join("multi-agent-config")
+                .join("secrets.key")
+        },
+        PathBuf::from,
+    )
+}
+
+/// Generate a new key file
+///
+/// # Arguments
+///
+/// * `path` - Where to write the key
+/// * `force` - Overwrite an existing key file (the old key is backed up)
+///
+/// # Errors
+///
+/// Returns error if the key file exists and `force` is not set, or it cannot
+/// be written
+pub fn generate_key_file(path: &Path, force: bool) -> Result<(), SecretsError> {
+    if path.exists() {
+        if !force {
+            return Err(SecretsError::KeyFileExists(path.to_path_buf()));
+        }
+        create_backup(path)?;
+    }
+
+    write_key_file(path, &KeyMaterial::Raw(generate_key()?))
+}
+
+/// Write raw key material to a key file with mode 0600
+fn write_key_file(path: &Path, key: &KeyMaterial) -> Result<(), SecretsError> {
+    let KeyMaterial::Raw(key) = key else {
+        return Ok(());
+    };
+    write_file_atomic(path, &encode_key(key), Some(0o600))
+        .map_err(|e| SecretsError::Io(std::io::Error::other(e.to_string())))
+}
+
+/// Find the key material for a derivation
+///
+/// # Arguments
+///
+/// * `kdf` - Derivation the secrets file uses
+/// * `file` - Secrets file, for error messages
+/// * `key_file` - Key file to read with [`Kdf::KeyFile`]
+///
+/// # Errors
+///
+/// Returns [`SecretsError::KeyMissing`] if the passphrase variable is unset
+/// or the key file does not exist
+pub fn resolve_key(kdf: Kdf, file: &Path, key_file: &Path) -> Result<KeyMaterial, SecretsError> {
+    match kdf {
+        Kdf::Pbkdf2Sha256 => env::var(PASSPHRASE_ENV)
+            .map(KeyMaterial::Passphrase)
+            .map_err(|_| SecretsError::KeyMissing {
+                file: file.to_path_buf(),
+                hint: format!("file is passphrase-encrypted; set {PASSPHRASE_ENV}"),
+            }),
+        Kdf::KeyFile => match fs::read_to_string(key_file) {
+            Ok(contents) => Ok(KeyMaterial::Raw(decode_key(&contents, key_file)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(SecretsError::KeyMissing {
+                file: file.to_path_buf(),
+                hint: format!(
+                    "key file {} not found; copy the team key there, set {KEY_FILE_ENV}, or run \
+                     'multi-agent-config secrets keygen' for a new file",
+                    key_file.display()
+                ),
+            }),
+            Err(e) => Err(e.into()),
+        },
+    }
+}
+
+/// Decrypted secrets file
+#[derive(Debug)]
+pub struct SecretsFile {
+    /// Location of the encrypted file
+    path: PathBuf,
+    /// Key derivation used when saving
+    kdf: Kdf,
+    /// Key file used with [`Kdf::KeyFile`]
+    key_file: PathBuf,
+    /// Secret name to value
+    values: BTreeMap<String, String>,
+}
+
+impl SecretsFile {
+    /// Load and decrypt an existing secrets file, using [`key_file_path`]
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file cannot be read or parsed, no key is
+    /// available, or decryption fails
+    pub fn load(path: &Path) -> Result<Self, SecretsError> {
+        Self::load_with_key_file(path, &key_file_path())
+    }
+
+    /// Load and decrypt an existing secrets file with a given key file
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the file cannot be read or parsed, no key is
+    /// available, or decryption fails
+    pub fn load_with_key_file(path: &Path, key_file: &Path) -> Result<Self, SecretsError> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                SecretsError::NotFound(path.to_path_buf())
+            } else {
+                e.into()
+            }
+        })?;
+        let envelope: Envelope =
+            toml::from_str(&contents).map_err(|e| SecretsError::format(path, e.message()))?;
+
+        let key = resolve_key(envelope.kdf, path, key_file)?;
+        let plaintext = envelope.open(&key, path)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            kdf: envelope.kdf,
+            key_file: key_file.to_path_buf(),
+            values: parse_plaintext(&plaintext, path)?,
+        })
+    }
+
+    /// Load a secrets file, or start an empty one if it doesn't exist
+    ///
+    /// New files are passphrase-encrypted when `MULTI_AGENT_CONFIG_PASSPHRASE`
+    /// is set, and use the key file otherwise.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if an existing file cannot be loaded
+    pub fn load_or_new(path: &Path) -> Result<Self, SecretsError> {
+        Self::load_or_new_with_key_file(path, &key_file_path())
+    }
+
+    /// Like [`Self::load_or_new`], with a given key file
+    ///
+    /// # Errors
+    ///
+    /// Returns error if an existing file cannot be loaded
+    pub fn load_or_new_with_key_file(path: &Path, key_file: &Path) -> Result<Self, SecretsError> {
+        if path.exists() {
+            return Self::load_with_key_file(path, key_file);
+        }
+
+        let kdf = if env::var_os(PASSPHRASE_ENV).is_some() {
+            Kdf::Pbkdf2Sha256
+        } else {
+            Kdf::KeyFile
+        };
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            kdf,
+            key_file: key_file.to_path_buf(),
+            values: BTreeMap::new(),
+        })
+    }
+
+    /// Location of the encrypted file
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Key derivation used when saving
+    #[must_use]
+    pub const fn kdf(&self) -> Kdf {
+        self.kdf
+    }
+
+    /// All secrets, by name
+    #[must_use]
+    pub const fn values(&self) -> &BTreeMap<String, String> {
+        &self.values
+    }
+
+    /// Get a secret's value
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    /// Set a secret's value
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Remove a secret, returning whether it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        self.values.remove(name).is_some()
+    }
+
+    /// Secrets as editable TOML
+    #[must_use]
+    pub fn to_plaintext(&self) -> String {
+        toml::to_string(&self.values).unwrap_or_default()
+    }
+
+    /// Replace all secrets with the contents of edited TOML
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the text is not a table of string values; the
+    /// current secrets are left unchanged
+    pub fn replace_from_plaintext(&mut self, plaintext: &str) -> Result<(), SecretsError> {
+        self.values = parse_plaintext(plaintext, &self.path)?;
+        Ok(())
+    }
+
+    /// Encrypt and write the file with its current key
+    ///
+    /// # Errors
+    ///
+    /// Returns error if no key is available or the file cannot be written
+    pub fn save(&self) -> Result<(), SecretsError> {
+        let key = resolve_key(self.kdf, &self.path, &self.key_file)?;
+        self.write(&Envelope::seal(&self.to_plaintext(), &key)?)
+    }
+
+    /// Re-encrypt the file under a new key
+    ///
+    /// With [`Kdf::KeyFile`], a new key file replaces the current one (the
+    /// old key is backed up first). Other secrets files encrypted with the
+    /// old key file must be rotated before it is discarded. With
+    /// [`Kdf::Pbkdf2Sha256`], the new passphrase is read from
+    /// `MULTI_AGENT_CONFIG_NEW_PASSPHRASE`.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the new passphrase is missing, or the key or secrets
+    /// file cannot be written
+    pub fn rotate(&mut self, kdf: Kdf) -> Result<(), SecretsError> {
+        let key = match kdf {
+            Kdf::KeyFile => KeyMaterial::Raw(generate_key()?),
+            Kdf::Pbkdf2Sha256 => env::var(NEW_PASSPHRASE_ENV)
+                .map(KeyMaterial::Passphrase)
+                .map_err(|_| SecretsError::KeyMissing {
+                    file: self.path.clone(),
+                    hint: format!("set {NEW_PASSPHRASE_ENV} to the new passphrase"),
+                })?,
+        };
+
+        // Encrypt before touching the key file so a failure loses nothing
+        let envelope = Envelope::seal(&self.to_plaintext(), &key)?;
+
+        if kdf == Kdf::KeyFile {
+            if self.key_file.exists() {
+                create_backup(&self.key_file)?;
+            }
+            write_key_file(&self.key_file, &key)?;
+        }
+
+        self.write(&envelope)?;
+        self.kdf = kdf;
+        Ok(())
+    }
+
+    /// Write an envelope to the secrets file
+    fn write(&self, envelope: &Envelope) -> Result<(), SecretsError> {
+        let contents = toml::to_string(envelope)
+            .map_err(|e| SecretsError::format(&self.path, e.to_string()))?;
+        write_file_atomic(&self.path, &contents, Some(0o600))
+            .map_err(|e| SecretsError::Io(std::io::Error::other(e.to_string())))
+    }
+}
+
+/// Parse decrypted contents: a table of string values
+fn parse_plaintext(plaintext: &str, path: &Path) -> Result<BTreeMap<String, String>, SecretsError> {
+    toml::from_str(plaintext).map_err(|e| {
+        SecretsError::format(
+            path,
+            format!("expected NAME = \"value\" pairs: {}", e.message()),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_secrets_file_path() {
+        assert_eq!(
+            secrets_file_path(Path::new("/repo/config.toml")),
+            PathBuf::from("/repo/secrets.toml.enc")
+        );
+    }
+
+    #[test]
+    fn test_plaintext_round_trip() {
+        let mut file = SecretsFile {
+            path: PathBuf::from("secrets.toml.enc"),
+            kdf: Kdf::KeyFile,
+            key_file: PathBuf::from("secrets.key"),
+            values: BTreeMap::new(),
+        };
+        file.set("GITHUB_TOKEN", "ghp_value");
+        file.set("API_KEY", "sk-value");
+
+        let plaintext = file.to_plaintext();
+        file.replace_from_plaintext(&plaintext).unwrap();
+        assert_eq!(file.get("GITHUB_TOKEN"), Some("ghp_value"));
+        assert!(file.remove("API_KEY"));
+        assert!(!file.remove("API_KEY"));
+    }
+
+    #[test]
+    fn test_replace_rejects_non_strings() {
+        let mut file = SecretsFile {
+            path: PathBuf::from("secrets.toml.enc"),
+            kdf: Kdf::KeyFile,
+            key_file: PathBuf::from("secrets.key"),
+            values: BTreeMap::from([("KEEP".to_string(), "value".to_string())]),
+        };
+
+        let result = file.replace_from_plaintext("PORT = 8080\n");
+        assert!(matches!(result, Err(SecretsError::Format { .. })));
+        assert_eq!(file.get("KEEP"), Some("value"));
+    }
+
+    #[test]
+    fn test_generate_key_file_refuses_overwrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let key_path = temp_dir.path().join("secrets.key");
+
+        generate_key_file(&key_path, false).unwrap();
+        let first = fs::read_to_string(&key_path).unwrap();
+
+        assert!(matches!(
+            generate_key_file(&key_path, false),
+            Err(SecretsError::KeyFileExists(_))
+        ));
+
+        generate_key_file(&key_path, true).unwrap();
+        assert_ne!(fs::read_to_string(&key_path).unwrap(), first);
+        assert!(temp_dir.path().join("secrets.key.backup").exists());
+    }
+}
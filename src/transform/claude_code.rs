@@ -1,24 +1,156 @@
 //! Claude Code JSON transformer
 //!
-//! Claude Code uses the same format as opencode.ai
+//! Claude Code uses the same format as opencode.ai, with transport types
+//! and without per-server timeouts. Tool deny and auto-approve lists become
+//! permission rules in Claude Code's settings file.
 
 use crate::{
     config::types::{ServerConfig, ToolName},
-    transform::{filter::filter_servers_for_tool, opencode::OpencodeServer},
+    transform::{extra::json_entry, filter::filter_servers_for_tool, opencode::OpencodeServer},
 };
 use serde::Serialize;
-use std::collections::HashMap;
+use std::{collections::HashMap, path::PathBuf};
+
+/// Claude Code permission rules for MCP tools, `mcp__<server>__<tool>`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PermissionRules {
+    /// Tools that run without asking
+    pub allow: Vec<String>,
+
+    /// Tools that are never run
+    pub deny: Vec<String>,
+}
+
+impl PermissionRules {
+    /// Whether there are no rules
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Every rule, allow rules first
+    #[must_use]
+    pub fn all(&self) -> Vec<String> {
+        self.allow.iter().chain(&self.deny).cloned().collect()
+    }
+}
+
+/// Default location of Claude Code's user settings, `~/.claude/settings.json`
+#[must_use]
+pub fn default_settings_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".claude")
+        .join("settings.json")
+}
+
+/// Permission rules for the servers' `tools` lists
+///
+/// `auto_approve` becomes allow rules and `deny` deny rules; Claude Code
+/// cannot limit a server to an allow list.
+///
+/// # Arguments
+///
+/// * `servers` - All servers, under the names they are emitted with
+/// * `default_targets` - Default targets from settings
+#[must_use]
+#[allow(clippy::implicit_hasher)]
+pub fn claude_code_permissions(
+    servers: &HashMap<String, ServerConfig>,
+    default_targets: &[String],
+) -> PermissionRules {
+    let filtered = filter_servers_for_tool(servers, ToolName::ClaudeCode, default_targets);
+    let rule = |name: &str, tool: &str| format!("mcp__{name}__{tool}");
+
+    let mut rules = PermissionRules::default();
+    for (name, server) in &filtered {
+        let policy = server.tools();
+        rules
+            .allow
+            .extend(policy.auto_approve.iter().map(|tool| rule(name, tool)));
+        rules
+            .deny
+            .extend(policy.deny.iter().map(|tool| rule(name, tool)));
+    }
+    rules.allow.sort();
+    rules.deny.sort();
+    rules
+}
+
+/// Merge permission rules into Claude Code's settings
+///
+/// Rules in `managed`, written by an earlier compile, are removed first;
+/// everything else in the file is kept.
+///
+/// # Arguments
+///
+/// * `existing` - Current settings file content, if the file exists
+/// * `rules` - Rules to write
+/// * `managed` - Rules the previous compile wrote
+///
+/// # Returns
+///
+/// * `Ok(String)` - New settings file content
+/// * `Err(String)` - Existing settings are not a JSON object
+///
+/// # Errors
+///
+/// Returns error if the existing settings cannot be parsed, or `permissions`
+/// or its lists have the wrong type
+pub fn merge_permissions(
+    existing: Option<&str>,
+    rules: &PermissionRules,
+    managed: &[String],
+) -> Result<String, String> {
+    let mut settings: serde_json::Value = match existing {
+        Some(content) if !content.trim().is_empty() => serde_json::from_str(content)
+            .map_err(|e| format!("Cannot parse Claude Code settings: {e}"))?,
+        _ => serde_json::json!({}),
+    };
+    let permissions = settings
+        .as_object_mut()
+        .ok_or("Claude Code settings are not a JSON object")?
+        .entry("permissions")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or("'permissions' in Claude Code settings is not an object")?;
+
+    for (key, new) in [("allow", &rules.allow), ("deny", &rules.deny)] {
+        let list = permissions
+            .entry(key)
+            .or_insert_with(|| serde_json::json!([]))
+            .as_array_mut()
+            .ok_or_else(|| format!("'permissions.{key}' in Claude Code settings is not a list"))?;
+        list.retain(|rule| {
+            !rule
+                .as_str()
+                .is_some_and(|rule| managed.iter().any(|m| m == rule))
+        });
+        for rule in new {
+            if !list.iter().any(|existing| existing == rule.as_str()) {
+                list.push(rule.clone().into());
+            }
+        }
+    }
+
+    let mut content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("JSON serialization error: {e}"))?;
+    content.push('\n');
+    Ok(content)
+}
 
 /// Claude Code configuration output structure (same as opencode.ai)
 #[derive(Debug, Serialize)]
 struct ClaudeCodeConfig {
     /// MCP servers for Claude Code
-    mcp: HashMap<String, OpencodeServer>,
+    mcp: HashMap<String, serde_json::Value>,
 }
 
 /// Transform servers to Claude Code JSON format
 ///
-/// Claude Code uses the same MCP configuration format as opencode.ai.
+/// Claude Code uses the same MCP configuration format as opencode.ai, except
+/// that `type` is the transport: `stdio`, `sse` or `http`, and there is no
+/// `timeout`; Claude Code only reads timeouts from its environment.
 ///
 /// # Arguments
 ///
@@ -44,15 +176,24 @@ pub fn transform_for_claude_code(
     let mut claude_servers = HashMap::new();
 
     for (name, server) in filtered {
-        let opencode_server = match server {
+        // Same fields as opencode.ai, but the type names the transport
+        let server_type = server.transport().to_string();
+        let opencode_server = match &server {
             ServerConfig::Stdio(stdio) => {
-                OpencodeServer::Local(crate::transform::opencode::transform_stdio_server(&stdio))
+                let mut local = crate::transform::opencode::transform_stdio_server(stdio);
+                local.server_type = server_type;
+                local.timeout = None;
+                OpencodeServer::Local(local)
             }
             ServerConfig::Http(http) => {
-                OpencodeServer::Remote(crate::transform::opencode::transform_http_server(&http))
+                let mut remote = crate::transform::opencode::transform_http_server(http);
+                remote.server_type = server_type;
+                remote.timeout = None;
+                OpencodeServer::Remote(remote)
             }
         };
-        claude_servers.insert(name, opencode_server);
+        let entry = json_entry(&opencode_server, server.extra_for(ToolName::ClaudeCode))?;
+        claude_servers.insert(name, entry);
     }
 
     let claude_config = ClaudeCodeConfig {
@@ -67,7 +208,7 @@ pub fn transform_for_claude_code(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::types::{HttpServerConfig, StdioServerConfig};
+    use crate::config::types::{HttpServerConfig, StdioServerConfig, ToolPolicy, Transport};
 
     fn create_stdio_server(command: &str, args: Vec<String>, targets: Vec<String>) -> ServerConfig {
         ServerConfig::Stdio(StdioServerConfig {
@@ -80,6 +221,7 @@ mod tests {
             auto_approve: None,
             startup_timeout_sec: None,
             tool_timeout_sec: None,
+            ..Default::default()
         })
     }
 
@@ -101,7 +243,7 @@ mod tests {
         let json = result.unwrap();
         assert!(json.contains("mcp"));
         assert!(json.contains("test"));
-        assert!(json.contains("\"type\": \"local\""));
+        assert!(json.contains("\"type\": \"stdio\""));
         assert!(json.contains("npx"));
     }
 
@@ -115,6 +257,7 @@ mod tests {
                 bearer_token: Some("token".to_string()),
                 enabled: true,
                 targets: vec!["all".to_string()],
+                ..Default::default()
             }),
         );
 
@@ -122,10 +265,27 @@ mod tests {
         assert!(result.is_ok());
 
         let json = result.unwrap();
-        assert!(json.contains("\"type\": \"remote\""));
+        assert!(json.contains("\"type\": \"http\""));
         assert!(json.contains("https://example.com"));
     }
 
+    #[test]
+    fn test_transform_claude_code_sse() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "events".to_string(),
+            ServerConfig::Http(HttpServerConfig {
+                url: "https://example.com/sse".to_string(),
+                transport: Some(Transport::Sse),
+                ..Default::default()
+            }),
+        );
+
+        let json = transform_for_claude_code(&servers, &[]).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["mcp"]["events"]["type"], "sse");
+    }
+
     #[test]
     fn test_transform_claude_code_filters_correctly() {
         let mut servers = HashMap::new();
@@ -156,4 +316,66 @@ mod tests {
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
         assert!(parsed["mcp"].as_object().unwrap().is_empty());
     }
+
+    #[test]
+    fn test_claude_code_permissions() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "github".to_string(),
+            ServerConfig::Stdio(StdioServerConfig {
+                command: "github-mcp".to_string(),
+                targets: vec!["claude-code".to_string()],
+                tools: ToolPolicy {
+                    deny: vec!["delete_repo".to_string()],
+                    auto_approve: vec!["search_code".to_string(), "get_issue".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        );
+
+        let rules = claude_code_permissions(&servers, &[]);
+        assert_eq!(
+            rules.allow,
+            ["mcp__github__get_issue", "mcp__github__search_code"]
+        );
+        assert_eq!(rules.deny, ["mcp__github__delete_repo"]);
+    }
+
+    #[test]
+    fn test_merge_permissions() {
+        let existing = r#"{
+  "model": "opus",
+  "permissions": {
+    "allow": ["Bash(ls)", "mcp__old__tool"],
+    "deny": ["mcp__github__delete_repo"]
+  }
+}"#;
+        let rules = PermissionRules {
+            allow: vec!["mcp__github__search_code".to_string()],
+            deny: vec!["mcp__github__delete_repo".to_string()],
+        };
+        let managed = ["mcp__old__tool".to_string()];
+
+        let merged = merge_permissions(Some(existing), &rules, &managed).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&merged).unwrap();
+        assert_eq!(parsed["model"], "opus");
+        assert_eq!(
+            parsed["permissions"]["allow"],
+            serde_json::json!(["Bash(ls)", "mcp__github__search_code"])
+        );
+        assert_eq!(
+            parsed["permissions"]["deny"],
+            serde_json::json!(["mcp__github__delete_repo"])
+        );
+
+        let created = merge_permissions(None, &rules, &[]).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&created).unwrap();
+        assert_eq!(
+            parsed["permissions"]["allow"],
+            serde_json::json!(["mcp__github__search_code"])
+        );
+
+        assert!(merge_permissions(Some("[]"), &rules, &[]).is_err());
+    }
 }
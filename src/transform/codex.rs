@@ -2,16 +2,19 @@
 
 use crate::{
     config::types::{HttpServerConfig, ServerConfig, StdioServerConfig, ToolName},
-    transform::filter::filter_servers_for_tool,
+    transform::{
+        extra::toml_entry, filter::filter_servers_for_tool, headers::custom_headers,
+        runtime::exact_runtime_ref,
+    },
 };
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// Codex configuration output structure
 #[derive(Debug, Serialize)]
 struct CodexConfig {
     /// MCP servers for Codex
-    mcp_servers: HashMap<String, CodexServer>,
+    mcp_servers: HashMap<String, toml::Value>,
 }
 
 /// Codex server configuration (STDIO or HTTP)
@@ -34,14 +37,31 @@ struct CodexStdioServer {
     #[serde(skip_serializing_if = "Option::is_none")]
     args: Option<Vec<String>>,
 
-    /// Startup timeout in seconds (optional, Codex-specific)
+    /// Working directory (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+
+    /// Startup timeout in seconds (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     startup_timeout_sec: Option<u32>,
 
-    /// Tool timeout in seconds (optional, Codex-specific)
+    /// Tool timeout in seconds (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_timeout_sec: Option<u32>,
 
+    /// Environment variables forwarded from Codex's own environment
+    /// (optional, runtime references)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    env_vars: Option<Vec<String>>,
+
+    /// Only tools offered (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled_tools: Option<Vec<String>>,
+
+    /// Tools never offered (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disabled_tools: Option<Vec<String>>,
+
     /// Environment variables (optional, as separate table in TOML)
     #[serde(skip_serializing_if = "Option::is_none")]
     env: Option<HashMap<String, String>>,
@@ -56,6 +76,41 @@ struct CodexHttpServer {
     /// Bearer token for authentication (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     bearer_token: Option<String>,
+
+    /// Environment variable holding the bearer token (optional, runtime
+    /// reference)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bearer_token_env_var: Option<String>,
+
+    /// Static HTTP headers (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    http_headers: Option<BTreeMap<String, String>>,
+
+    /// HTTP headers read from environment variables (optional, runtime
+    /// references)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    env_http_headers: Option<BTreeMap<String, String>>,
+
+    /// Startup timeout in seconds (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    startup_timeout_sec: Option<u32>,
+
+    /// Tool timeout in seconds (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tool_timeout_sec: Option<u32>,
+
+    /// Only tools offered (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enabled_tools: Option<Vec<String>>,
+
+    /// Tools never offered (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    disabled_tools: Option<Vec<String>>,
+}
+
+/// Non-empty tool list, for `enabled_tools` and `disabled_tools`
+fn tool_list(tools: &[String]) -> Option<Vec<String>> {
+    Some(tools.to_vec()).filter(|tools| !tools.is_empty())
 }
 
 /// Transform servers to Codex TOML format
@@ -86,11 +141,12 @@ pub fn transform_for_codex(
     let mut codex_servers = HashMap::new();
 
     for (name, server) in filtered {
-        let codex_server = match server {
-            ServerConfig::Stdio(stdio) => CodexServer::Stdio(transform_stdio_server(&stdio)),
-            ServerConfig::Http(http) => CodexServer::Http(transform_http_server(&http)),
+        let codex_server = match &server {
+            ServerConfig::Stdio(stdio) => CodexServer::Stdio(transform_stdio_server(stdio)),
+            ServerConfig::Http(http) => CodexServer::Http(transform_http_server(http)),
         };
-        codex_servers.insert(name, codex_server);
+        let entry = toml_entry(&codex_server, server.extra_for(ToolName::Codex))?;
+        codex_servers.insert(name, entry);
     }
 
     let codex_config = CodexConfig {
@@ -102,7 +158,26 @@ pub fn transform_for_codex(
 }
 
 /// Transform a STDIO server to Codex format
+///
+/// Env entries that are runtime references to a variable of the same name
+/// are emitted through `env_vars` instead of the `env` table.
 fn transform_stdio_server(stdio: &StdioServerConfig) -> CodexStdioServer {
+    let mut env_vars = Vec::new();
+    let env = stdio.env.as_ref().map(|env| {
+        env.iter()
+            .filter(|(key, value)| {
+                if exact_runtime_ref(value) == Some(key.as_str()) {
+                    env_vars.push((*key).clone());
+                    false
+                } else {
+                    true
+                }
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<HashMap<_, _>>()
+    });
+    env_vars.sort();
+
     CodexStdioServer {
         command: stdio.command.clone(),
         args: if stdio.args.is_empty() {
@@ -110,23 +185,59 @@ fn transform_stdio_server(stdio: &StdioServerConfig) -> CodexStdioServer {
         } else {
             Some(stdio.args.clone())
         },
+        cwd: stdio.cwd.clone(),
         startup_timeout_sec: stdio.startup_timeout_sec,
         tool_timeout_sec: stdio.tool_timeout_sec,
-        env: stdio.env.clone(),
+        env_vars: if env_vars.is_empty() {
+            None
+        } else {
+            Some(env_vars)
+        },
+        enabled_tools: tool_list(&stdio.tools.allow),
+        disabled_tools: tool_list(&stdio.tools.deny),
+        env: env.filter(|env| !env.is_empty()),
     }
 }
 
 /// Transform an HTTP server to Codex format
+///
+/// A bearer token that is a runtime reference becomes
+/// `bearer_token_env_var`; other headers go to `http_headers`, or to
+/// `env_http_headers` when their value is a runtime reference.
 fn transform_http_server(http: &HttpServerConfig) -> CodexHttpServer {
+    let token = http.bearer();
+    let token_env_var = token.and_then(exact_runtime_ref);
+
+    let mut http_headers = BTreeMap::new();
+    let mut env_http_headers = BTreeMap::new();
+    for (name, value) in custom_headers(http) {
+        match exact_runtime_ref(&value) {
+            Some(var) => env_http_headers.insert(name, var.to_string()),
+            None => http_headers.insert(name, value),
+        };
+    }
+
     CodexHttpServer {
         url: http.url.clone(),
-        bearer_token: http.bearer_token.clone(),
+        bearer_token: if token_env_var.is_some() {
+            None
+        } else {
+            token.map(str::to_string)
+        },
+        bearer_token_env_var: token_env_var.map(str::to_string),
+        http_headers: Some(http_headers).filter(|headers| !headers.is_empty()),
+        env_http_headers: Some(env_http_headers).filter(|headers| !headers.is_empty()),
+        startup_timeout_sec: http.startup_timeout_sec,
+        tool_timeout_sec: http.tool_timeout_sec,
+        enabled_tools: tool_list(&http.tools.allow),
+        disabled_tools: tool_list(&http.tools.deny),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::types::HttpAuth;
 
     fn create_stdio_server(command: &str, args: Vec<String>, targets: Vec<String>) -> ServerConfig {
         ServerConfig::Stdio(StdioServerConfig {
@@ -139,6 +250,7 @@ mod tests {
             auto_approve: None,
             startup_timeout_sec: None,
             tool_timeout_sec: None,
+            ..Default::default()
         })
     }
 
@@ -152,6 +264,7 @@ mod tests {
             bearer_token,
             enabled: true,
             targets,
+            ..Default::default()
         })
     }
 
@@ -194,6 +307,7 @@ mod tests {
                 auto_approve: None,
                 startup_timeout_sec: Some(30),
                 tool_timeout_sec: Some(60),
+                ..Default::default()
             }),
         );
 
@@ -247,6 +361,7 @@ mod tests {
                 auto_approve: None,
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                ..Default::default()
             }),
         );
 
@@ -288,4 +403,73 @@ mod tests {
         // Empty config should have empty mcp_servers table
         assert!(toml_str.contains("mcp_servers"));
     }
+
+    #[test]
+    fn test_transform_codex_runtime_refs() {
+        let mut env_vars = HashMap::new();
+        env_vars.insert("GITHUB_TOKEN".to_string(), "${GITHUB_TOKEN}".to_string());
+        env_vars.insert("LOG_LEVEL".to_string(), "debug".to_string());
+
+        let mut servers = HashMap::new();
+        servers.insert(
+            "local".to_string(),
+            ServerConfig::Stdio(StdioServerConfig {
+                command: "npx".to_string(),
+                env: Some(env_vars),
+                ..Default::default()
+            }),
+        );
+        servers.insert(
+            "remote".to_string(),
+            create_http_server(
+                "https://api.example.com",
+                Some("${API_TOKEN}".to_string()),
+                vec!["all".to_string()],
+            ),
+        );
+
+        let toml_str = transform_for_codex(&servers, &[]).unwrap();
+        let parsed: toml::Value = toml::from_str(&toml_str).unwrap();
+
+        let local = &parsed["mcp_servers"]["local"];
+        assert_eq!(local["env_vars"].as_array().unwrap().len(), 1);
+        assert_eq!(local["env_vars"][0].as_str(), Some("GITHUB_TOKEN"));
+        assert_eq!(local["env"]["LOG_LEVEL"].as_str(), Some("debug"));
+        assert!(local["env"].get("GITHUB_TOKEN").is_none());
+
+        let remote = &parsed["mcp_servers"]["remote"];
+        assert_eq!(remote["bearer_token_env_var"].as_str(), Some("API_TOKEN"));
+        assert!(remote.get("bearer_token").is_none());
+    }
+
+    #[test]
+    fn test_transform_codex_headers_and_auth() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "remote".to_string(),
+            ServerConfig::Http(HttpServerConfig {
+                url: "https://api.example.com".to_string(),
+                headers: Some(HashMap::from([
+                    ("X-Team".to_string(), "platform".to_string()),
+                    ("X-API-Key".to_string(), "${API_KEY}".to_string()),
+                ])),
+                auth: Some(HttpAuth::Bearer {
+                    token: "token123".to_string(),
+                }),
+                ..Default::default()
+            }),
+        );
+
+        let toml_str = transform_for_codex(&servers, &[]).unwrap();
+        let parsed: toml::Value = toml::from_str(&toml_str).unwrap();
+
+        let remote = &parsed["mcp_servers"]["remote"];
+        assert_eq!(remote["bearer_token"].as_str(), Some("token123"));
+        assert_eq!(remote["http_headers"]["X-Team"].as_str(), Some("platform"));
+        assert_eq!(
+            remote["env_http_headers"]["X-API-Key"].as_str(),
+            Some("API_KEY")
+        );
+        assert!(remote["http_headers"].get("X-API-Key").is_none());
+    }
 }
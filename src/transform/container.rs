@@ -0,0 +1,149 @@
+//! Container servers
+//!
+//! A server with `container` runs its image through the container CLI. The
+//! `run` command line is built before the rest of the pipeline, so every
+//! tool, launcher and working directory fallback sees an ordinary command.
+
+use crate::config::types::{ContainerConfig, ServerConfig};
+
+/// Arguments for `<runtime> run` that start a container server
+///
+/// The container is interactive (`-i`, for stdio) and removed on exit.
+/// Environment variables are passed with `-e NAME`, so their values come
+/// from the runtime's own environment and never appear on the command line.
+///
+/// # Arguments
+///
+/// * `container` - Container settings
+/// * `env_keys` - Environment variables to pass into the container
+/// * `args` - Arguments for the image's entrypoint
+#[must_use]
+pub fn container_args(
+    container: &ContainerConfig,
+    env_keys: &[&str],
+    args: &[String],
+) -> Vec<String> {
+    let mut run = vec!["run".to_string(), "-i".to_string(), "--rm".to_string()];
+    if let Some(pull) = container.pull {
+        run.push(format!("--pull={}", pull.as_str()));
+    }
+    if let Some(network) = &container.network {
+        run.push("--network".to_string());
+        run.push(network.clone());
+    }
+    for mount in &container.mounts {
+        run.push("-v".to_string());
+        run.push(mount.clone());
+    }
+    for key in env_keys {
+        run.push("-e".to_string());
+        run.push((*key).to_string());
+    }
+    run.push(container.image.clone());
+    run.extend(args.iter().cloned());
+    run
+}
+
+/// Turn a container server into a `<runtime> run` command
+///
+/// The container's `env` is merged into the server's, replacing keys both
+/// set, and every variable is passed into the container by name.
+pub fn apply_container(server: &mut ServerConfig) {
+    let ServerConfig::Stdio(stdio) = server else {
+        return;
+    };
+    let Some(container) = stdio.container.take() else {
+        return;
+    };
+
+    if !container.env.is_empty() {
+        let env = stdio.env.get_or_insert_with(Default::default);
+        env.extend(container.env.clone());
+    }
+    let mut env_keys: Vec<&str> = stdio
+        .env
+        .iter()
+        .flatten()
+        .map(|(k, _)| k.as_str())
+        .collect();
+    env_keys.sort_unstable();
+
+    stdio.args = container_args(&container, &env_keys, &stdio.args);
+    stdio.command = container.runtime.command().to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::{ContainerRuntime, PullPolicy, StdioServerConfig};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_container_args() {
+        let container = ContainerConfig {
+            image: "ghcr.io/github/github-mcp-server".to_string(),
+            mounts: vec!["/home/me/src:/src:ro".to_string()],
+            network: Some("none".to_string()),
+            pull: Some(PullPolicy::Missing),
+            ..Default::default()
+        };
+        assert_eq!(
+            container_args(&container, &["GITHUB_TOKEN"], &["stdio".to_string()]),
+            [
+                "run",
+                "-i",
+                "--rm",
+                "--pull=missing",
+                "--network",
+                "none",
+                "-v",
+                "/home/me/src:/src:ro",
+                "-e",
+                "GITHUB_TOKEN",
+                "ghcr.io/github/github-mcp-server",
+                "stdio",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_apply_container_passes_env_by_name() {
+        let mut server = ServerConfig::Stdio(StdioServerConfig {
+            args: vec!["stdio".to_string()],
+            env: Some(HashMap::from([(
+                "LOG_LEVEL".to_string(),
+                "debug".to_string(),
+            )])),
+            container: Some(ContainerConfig {
+                image: "mcp/github".to_string(),
+                runtime: ContainerRuntime::Podman,
+                env: HashMap::from([("GITHUB_TOKEN".to_string(), "secret".to_string())]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        apply_container(&mut server);
+
+        let ServerConfig::Stdio(stdio) = &server else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(stdio.command, "podman");
+        assert_eq!(
+            stdio.args,
+            [
+                "run",
+                "-i",
+                "--rm",
+                "-e",
+                "GITHUB_TOKEN",
+                "-e",
+                "LOG_LEVEL",
+                "mcp/github",
+                "stdio"
+            ]
+        );
+        assert!(!stdio.args.iter().any(|arg| arg.contains("secret")));
+        assert_eq!(stdio.env.as_ref().unwrap()["GITHUB_TOKEN"], "secret");
+        assert!(stdio.container.is_none());
+    }
+}
@@ -2,7 +2,7 @@
 
 use crate::{
     config::types::{ServerConfig, StdioServerConfig, ToolName},
-    transform::filter::filter_servers_for_tool,
+    transform::{extra::json_entry, filter::filter_servers_for_tool},
 };
 use serde::Serialize;
 use std::collections::HashMap;
@@ -12,7 +12,7 @@ use std::collections::HashMap;
 struct CursorConfig {
     /// MCP servers for Cursor
     #[serde(rename = "mcpServers")]
-    mcp_servers: HashMap<String, CursorServer>,
+    mcp_servers: HashMap<String, serde_json::Value>,
 }
 
 /// Cursor server configuration
@@ -66,11 +66,12 @@ pub fn transform_for_cursor(
 
     for (name, server) in filtered {
         // Cursor only supports STDIO servers, skip HTTP
-        if let ServerConfig::Stdio(stdio) = server {
-            let cursor_server = transform_stdio_server(&stdio);
-            cursor_servers.insert(name, cursor_server);
+        if let ServerConfig::Stdio(stdio) = &server {
+            let cursor_server = transform_stdio_server(stdio);
+            let entry = json_entry(&cursor_server, server.extra_for(ToolName::Cursor))?;
+            cursor_servers.insert(name, entry);
         }
-        // HTTP servers silently skipped for Cursor
+        // HTTP servers are skipped; validation warns when one targets Cursor
     }
 
     let cursor_config = CursorConfig {
@@ -83,13 +84,24 @@ pub fn transform_for_cursor(
 }
 
 /// Transform a STDIO server to Cursor format
+///
+/// `tools.auto_approve` is added to `autoApprove`; Cursor has no allow or
+/// deny lists.
 fn transform_stdio_server(stdio: &StdioServerConfig) -> CursorServer {
+    let mut auto_approve = stdio.auto_approve.clone();
+    for tool in &stdio.tools.auto_approve {
+        let approved = auto_approve.get_or_insert_with(Vec::new);
+        if !approved.contains(tool) {
+            approved.push(tool.clone());
+        }
+    }
+
     CursorServer {
         command: stdio.command.clone(),
         args: stdio.args.clone(),
         env: stdio.env.clone(),
         disabled: stdio.disabled,
-        auto_approve: stdio.auto_approve.clone(),
+        auto_approve,
     }
 }
 
@@ -108,6 +120,7 @@ mod tests {
             auto_approve: None,
             startup_timeout_sec: None,
             tool_timeout_sec: None,
+            ..Default::default()
         })
     }
 
@@ -147,6 +160,7 @@ mod tests {
                 auto_approve: None,
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                ..Default::default()
             }),
         );
 
@@ -174,6 +188,7 @@ mod tests {
                 auto_approve: Some(vec!["tool1".to_string()]),
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                ..Default::default()
             }),
         );
 
@@ -203,6 +218,7 @@ mod tests {
                 bearer_token: None,
                 enabled: true,
                 targets: vec!["all".to_string()],
+                ..Default::default()
             }),
         );
 
@@ -242,6 +258,7 @@ mod tests {
                 auto_approve: None,
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                ..Default::default()
             }),
         );
 
@@ -0,0 +1,83 @@
+//! Working directories for tools without a `cwd` field
+//!
+//! Codex starts servers in `cwd` itself. For other tools the command can be
+//! run through `sh -c`, which changes directory and then execs it, as set by
+//! `settings.cwd_fallback`.
+
+use crate::config::{
+    capabilities::capabilities,
+    types::{CwdFallback, ServerConfig, ToolName},
+};
+
+/// Script run by `sh -c`: `$0` is the directory, the rest the command line
+pub const CWD_WRAPPER: &str = r#"cd "$0" && exec "$@""#;
+
+/// Prepare a server's working directory for a tool
+///
+/// Leaves `cwd` alone when the tool supports it. Otherwise drops it, after
+/// wrapping the command in `sh -c` with [`CwdFallback::Wrap`].
+pub fn apply_cwd(server: &mut ServerConfig, tool: ToolName, fallback: CwdFallback) {
+    let ServerConfig::Stdio(stdio) = server else {
+        return;
+    };
+    if capabilities(tool).is_some_and(|caps| caps.supports_field("cwd")) {
+        return;
+    }
+    let Some(cwd) = stdio.cwd.take() else {
+        return;
+    };
+
+    if fallback == CwdFallback::Wrap {
+        let command = std::mem::replace(&mut stdio.command, "sh".to_string());
+        let mut args = vec!["-c".to_string(), CWD_WRAPPER.to_string(), cwd, command];
+        args.append(&mut stdio.args);
+        stdio.args = args;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::StdioServerConfig;
+
+    fn server() -> ServerConfig {
+        ServerConfig::Stdio(StdioServerConfig {
+            command: "node".to_string(),
+            args: vec!["server.js".to_string()],
+            cwd: Some("/srv/mcp".to_string()),
+            ..Default::default()
+        })
+    }
+
+    fn stdio(server: &ServerConfig) -> &StdioServerConfig {
+        match server {
+            ServerConfig::Stdio(stdio) => stdio,
+            ServerConfig::Http(_) => panic!("expected stdio server"),
+        }
+    }
+
+    #[test]
+    fn test_apply_cwd_native() {
+        let mut codex = server();
+        apply_cwd(&mut codex, ToolName::Codex, CwdFallback::Wrap);
+        assert_eq!(stdio(&codex).cwd.as_deref(), Some("/srv/mcp"));
+        assert_eq!(stdio(&codex).command, "node");
+    }
+
+    #[test]
+    fn test_apply_cwd_wrap_and_ignore() {
+        let mut cursor = server();
+        apply_cwd(&mut cursor, ToolName::Cursor, CwdFallback::Wrap);
+        assert_eq!(stdio(&cursor).command, "sh");
+        assert_eq!(
+            stdio(&cursor).args,
+            ["-c", CWD_WRAPPER, "/srv/mcp", "node", "server.js"]
+        );
+        assert_eq!(stdio(&cursor).cwd, None);
+
+        let mut opencode = server();
+        apply_cwd(&mut opencode, ToolName::Opencode, CwdFallback::Ignore);
+        assert_eq!(stdio(&opencode).command, "node");
+        assert_eq!(stdio(&opencode).cwd, None);
+    }
+}
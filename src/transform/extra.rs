@@ -0,0 +1,128 @@
+//! Raw `extra` fields merged into generated server entries
+//!
+//! A server's `extra.<tool>` table is emitted verbatim into that tool's
+//! entry, converted from TOML to JSON for the JSON formats. Extra keys
+//! replace generated ones; validation warns when that happens.
+
+use serde::Serialize;
+
+/// Serialize a JSON server entry with raw extra fields merged in
+///
+/// # Errors
+///
+/// Returns error if the entry cannot be serialized
+pub fn json_entry<T: Serialize>(
+    entry: &T,
+    extra: Option<&toml::Table>,
+) -> Result<serde_json::Value, String> {
+    let mut value =
+        serde_json::to_value(entry).map_err(|e| format!("JSON serialization error: {e}"))?;
+    if let (Some(object), Some(extra)) = (value.as_object_mut(), extra) {
+        for (key, raw) in extra {
+            object.insert(key.clone(), toml_to_json(raw));
+        }
+    }
+    Ok(value)
+}
+
+/// Serialize a TOML server entry with raw extra fields merged in
+///
+/// # Errors
+///
+/// Returns error if the entry cannot be serialized
+pub fn toml_entry<T: Serialize>(
+    entry: &T,
+    extra: Option<&toml::Table>,
+) -> Result<toml::Value, String> {
+    let mut value =
+        toml::Value::try_from(entry).map_err(|e| format!("TOML serialization error: {e}"))?;
+    if let (Some(table), Some(extra)) = (value.as_table_mut(), extra) {
+        for (key, raw) in extra {
+            table.insert(key.clone(), raw.clone());
+        }
+    }
+    Ok(value)
+}
+
+/// Convert a TOML value to JSON; datetimes become strings
+#[must_use]
+pub fn toml_to_json(value: &toml::Value) -> serde_json::Value {
+    match value {
+        toml::Value::String(s) => s.clone().into(),
+        toml::Value::Integer(i) => (*i).into(),
+        toml::Value::Float(f) => serde_json::Number::from_f64(*f)
+            .map_or(serde_json::Value::Null, serde_json::Value::Number),
+        toml::Value::Boolean(b) => (*b).into(),
+        toml::Value::Datetime(dt) => dt.to_string().into(),
+        toml::Value::Array(items) => items.iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => table
+            .iter()
+            .map(|(key, value)| (key.clone(), toml_to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Entry {
+        command: String,
+        timeout: u32,
+    }
+
+    fn extra() -> toml::Table {
+        toml::from_str(
+            r#"
+timeout = 60
+enabled_tools = ["search", "read"]
+oauth = { client_id = "abc", scopes = ["repo"] }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_json_entry_merges_extra() {
+        let entry = Entry {
+            command: "npx".to_string(),
+            timeout: 10,
+        };
+        let value = json_entry(&entry, Some(&extra())).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "command": "npx",
+                "timeout": 60,
+                "enabled_tools": ["search", "read"],
+                "oauth": { "client_id": "abc", "scopes": ["repo"] },
+            })
+        );
+    }
+
+    #[test]
+    fn test_toml_entry_merges_extra() {
+        let entry = Entry {
+            command: "npx".to_string(),
+            timeout: 10,
+        };
+        let value = toml_entry(&entry, Some(&extra())).unwrap();
+        assert_eq!(value["timeout"].as_integer(), Some(60));
+        assert_eq!(value["oauth"]["client_id"].as_str(), Some("abc"));
+        assert_eq!(value["command"].as_str(), Some("npx"));
+
+        let plain = toml_entry(&entry, None).unwrap();
+        assert_eq!(plain["timeout"].as_integer(), Some(10));
+    }
+
+    #[test]
+    fn test_toml_to_json_datetime() {
+        let table: toml::Table = toml::from_str("since = 2025-10-12").unwrap();
+        assert_eq!(
+            toml_to_json(&table["since"]),
+            serde_json::json!("2025-10-12")
+        );
+    }
+}
@@ -109,6 +109,7 @@ mod tests {
             auto_approve: None,
             startup_timeout_sec: None,
             tool_timeout_sec: None,
+            ..Default::default()
         })
     }
 
@@ -118,6 +119,7 @@ mod tests {
             bearer_token: None,
             enabled,
             targets,
+            ..Default::default()
         })
     }
 
@@ -0,0 +1,106 @@
+//! HTTP headers and authentication for remote servers
+
+use crate::config::types::{HttpAuth, HttpServerConfig};
+use base64::{Engine, engine::general_purpose::STANDARD};
+use std::collections::BTreeMap;
+
+/// Header carrying bearer and basic credentials
+pub const AUTHORIZATION: &str = "Authorization";
+
+/// Headers other than a bearer token
+///
+/// The server's `headers` plus any basic or header-based `auth`. Tools with a
+/// dedicated bearer token setting take the token separately.
+#[must_use]
+pub fn custom_headers(http: &HttpServerConfig) -> BTreeMap<String, String> {
+    let mut headers: BTreeMap<String, String> = http
+        .headers
+        .iter()
+        .flatten()
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+
+    match &http.auth {
+        Some(HttpAuth::Basic { username, password }) => {
+            let credentials = STANDARD.encode(format!("{username}:{password}"));
+            headers.insert(AUTHORIZATION.to_string(), format!("Basic {credentials}"));
+        }
+        Some(HttpAuth::Header { name, value }) => {
+            headers.insert(name.clone(), value.clone());
+        }
+        Some(HttpAuth::Bearer { .. }) | None => {}
+    }
+
+    headers
+}
+
+/// Every header to send, including `Authorization: Bearer` for a token
+#[must_use]
+pub fn all_headers(http: &HttpServerConfig) -> BTreeMap<String, String> {
+    let mut headers = custom_headers(http);
+    if let Some(token) = http.bearer() {
+        headers.insert(AUTHORIZATION.to_string(), format!("Bearer {token}"));
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn server(auth: Option<HttpAuth>) -> HttpServerConfig {
+        HttpServerConfig {
+            url: "https://example.com/mcp".to_string(),
+            headers: Some(HashMap::from([(
+                "X-Tenant".to_string(),
+                "acme".to_string(),
+            )])),
+            auth,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_bearer_auth() {
+        let http = server(Some(HttpAuth::Bearer {
+            token: "tok".to_string(),
+        }));
+
+        assert_eq!(custom_headers(&http).len(), 1);
+        assert_eq!(all_headers(&http)[AUTHORIZATION], "Bearer tok");
+        assert_eq!(all_headers(&http)["X-Tenant"], "acme");
+    }
+
+    #[test]
+    fn test_bearer_token_shorthand() {
+        let http = HttpServerConfig {
+            bearer_token: Some("tok".to_string()),
+            ..server(None)
+        };
+        assert_eq!(all_headers(&http)[AUTHORIZATION], "Bearer tok");
+    }
+
+    #[test]
+    fn test_basic_auth() {
+        let http = server(Some(HttpAuth::Basic {
+            username: "user".to_string(),
+            password: "pass".to_string(),
+        }));
+
+        // base64("user:pass")
+        assert_eq!(custom_headers(&http)[AUTHORIZATION], "Basic dXNlcjpwYXNz");
+    }
+
+    #[test]
+    fn test_header_auth() {
+        let http = server(Some(HttpAuth::Header {
+            name: "X-API-Key".to_string(),
+            value: "key".to_string(),
+        }));
+
+        let headers = all_headers(&http);
+        assert_eq!(headers["X-API-Key"], "key");
+        assert!(!headers.contains_key(AUTHORIZATION));
+    }
+}
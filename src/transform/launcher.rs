@@ -0,0 +1,288 @@
+//! Generated launcher scripts for STDIO servers
+//!
+//! A launcher exports the server's environment and `PATH`, changes to its
+//! working directory, appends stderr to a log file and execs the real
+//! command. The tool is then pointed at the launcher, so every tool starts
+//! the server the same way. Runtime references are left to the shell.
+
+use super::runtime::RUNTIME_REF;
+use crate::config::types::{ServerConfig, StdioServerConfig, ToolName};
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+};
+
+/// Launcher script generated for one server and tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Launcher {
+    /// Server name in the unified configuration
+    pub server: String,
+
+    /// Where the script is written
+    pub path: PathBuf,
+
+    /// Script content
+    pub content: String,
+}
+
+/// Path of a server's launcher script for a tool
+///
+/// `<dir>/<tool>/<server>.sh`, with characters other than ASCII letters,
+/// digits, `-` and `_` in the server name replaced by `_`.
+#[must_use]
+pub fn launcher_path(dir: &Path, tool: ToolName, server: &str) -> PathBuf {
+    let file: String = server
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(tool.to_string()).join(format!("{file}.sh"))
+}
+
+/// Quote a value for `sh`, keeping runtime references expandable
+///
+/// Literal text is single-quoted; `${VAR}` references are double-quoted so
+/// the shell expands them when the launcher runs.
+#[must_use]
+pub fn shell_quote(value: &str) -> String {
+    let single = |text: &str| format!("'{}'", text.replace('\'', r"'\''"));
+
+    let mut quoted = String::new();
+    let mut last = 0;
+    for found in RUNTIME_REF.find_iter(value) {
+        if found.start() > last {
+            quoted.push_str(&single(&value[last..found.start()]));
+        }
+        quoted.push('"');
+        quoted.push_str(found.as_str());
+        quoted.push('"');
+        last = found.end();
+    }
+    if last < value.len() || quoted.is_empty() {
+        quoted.push_str(&single(&value[last..]));
+    }
+    quoted
+}
+
+/// Whether `key` can be exported by `sh`
+fn is_shell_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Build the launcher script for a STDIO server
+///
+/// # Arguments
+///
+/// * `name` - Server name
+/// * `tool` - Tool the launcher is generated for
+/// * `stdio` - Server, with the tool's overrides applied
+/// * `search_path` - `PATH` to export when the server's `env` has none
+/// * `log` - File stderr is appended to
+///
+/// # Returns
+///
+/// The script and a warning for each environment variable that cannot be
+/// exported
+#[must_use]
+pub fn launcher_script(
+    name: &str,
+    tool: ToolName,
+    stdio: &StdioServerConfig,
+    search_path: Option<&str>,
+    log: &Path,
+) -> (String, Vec<String>) {
+    let mut warnings = Vec::new();
+    let mut script = format!(
+        "#!/bin/sh\n# Launcher for MCP server '{name}', generated by multi-agent-config for \
+         {tool}.\n# Changes are overwritten by the next compile.\n"
+    );
+
+    let mut env: BTreeMap<&str, &str> = stdio
+        .env
+        .iter()
+        .flatten()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .collect();
+    if let Some(path) = search_path {
+        env.entry("PATH").or_insert(path);
+    }
+    for (key, value) in env {
+        if is_shell_name(key) {
+            let _ = writeln!(script, "export {key}={}", shell_quote(value));
+        } else {
+            warnings.push(format!(
+                "Server '{name}': env.{key} is not a valid shell variable name, leaving it out of \
+                 the launcher"
+            ));
+        }
+    }
+
+    if let Some(cwd) = &stdio.cwd {
+        let _ = writeln!(script, "cd {} || exit 1", shell_quote(cwd));
+    }
+
+    script.push_str("exec");
+    for word in std::iter::once(&stdio.command).chain(&stdio.args) {
+        script.push(' ');
+        script.push_str(&shell_quote(word));
+    }
+    let _ = writeln!(script, " 2>>{}", shell_quote(&log.to_string_lossy()));
+
+    (script, warnings)
+}
+
+/// Replace servers that use a launcher with a call to their script
+///
+/// A STDIO server uses a launcher when its `launcher` field is set, or
+/// `default` when it isn't. The server keeps its other fields; command,
+/// arguments, environment and working directory move into the script.
+///
+/// # Arguments
+///
+/// * `servers` - Servers selected for the tool, overrides applied
+/// * `tool` - Tool being generated
+/// * `default` - `launcher` from settings
+/// * `dir` - Directory launchers are written under
+/// * `search_path` - `search_path` from settings
+///
+/// # Returns
+///
+/// The generated launchers, sorted by server name, and warnings
+#[allow(clippy::implicit_hasher)]
+pub fn apply_launchers(
+    servers: &mut std::collections::HashMap<String, ServerConfig>,
+    tool: ToolName,
+    default: bool,
+    dir: &Path,
+    search_path: Option<&str>,
+) -> (Vec<Launcher>, Vec<String>) {
+    let mut launchers = Vec::new();
+    let mut warnings = Vec::new();
+
+    for (name, server) in servers.iter_mut() {
+        let ServerConfig::Stdio(stdio) = server else {
+            continue;
+        };
+        if !stdio.launcher.unwrap_or(default) {
+            continue;
+        }
+
+        let path = launcher_path(dir, tool, name);
+        let log = path.with_extension("log");
+        let (content, mut issues) = launcher_script(name, tool, stdio, search_path, &log);
+        warnings.append(&mut issues);
+
+        stdio.command = path.to_string_lossy().into_owned();
+        stdio.args.clear();
+        stdio.env = None;
+        stdio.cwd = None;
+        launchers.push(Launcher {
+            server: name.clone(),
+            path,
+            content,
+        });
+    }
+
+    launchers.sort_by(|a, b| a.server.cmp(&b.server));
+    warnings.sort();
+    (launchers, warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("plain"), "'plain'");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("${TOKEN}"), "\"${TOKEN}\"");
+        assert_eq!(shell_quote("Bearer ${TOKEN}!"), "'Bearer '\"${TOKEN}\"'!'");
+    }
+
+    #[test]
+    fn test_launcher_script() {
+        let stdio = StdioServerConfig {
+            command: "npx".to_string(),
+            args: vec!["-y".to_string(), "server".to_string()],
+            cwd: Some("/srv/mcp".to_string()),
+            env: Some(HashMap::from([
+                ("TOKEN".to_string(), "${GITHUB_TOKEN}".to_string()),
+                ("BAD-NAME".to_string(), "x".to_string()),
+            ])),
+            ..Default::default()
+        };
+        let (script, warnings) = launcher_script(
+            "github",
+            ToolName::Codex,
+            &stdio,
+            Some("/usr/bin:/bin"),
+            Path::new("/state/github.log"),
+        );
+
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.ends_with(
+            "export PATH='/usr/bin:/bin'\nexport TOKEN=\"${GITHUB_TOKEN}\"\ncd '/srv/mcp' || exit \
+             1\nexec 'npx' '-y' 'server' 2>>'/state/github.log'\n"
+        ));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("env.BAD-NAME"));
+    }
+
+    #[test]
+    fn test_apply_launchers() {
+        let mut servers = HashMap::from([
+            (
+                "github".to_string(),
+                ServerConfig::Stdio(StdioServerConfig {
+                    command: "github-mcp".to_string(),
+                    args: vec!["--stdio".to_string()],
+                    ..Default::default()
+                }),
+            ),
+            (
+                "local files".to_string(),
+                ServerConfig::Stdio(StdioServerConfig {
+                    command: "files-mcp".to_string(),
+                    launcher: Some(false),
+                    ..Default::default()
+                }),
+            ),
+        ]);
+
+        let dir = Path::new("/state/launchers");
+        let (launchers, warnings) =
+            apply_launchers(&mut servers, ToolName::Cursor, true, dir, None);
+        assert!(warnings.is_empty());
+        assert_eq!(launchers.len(), 1);
+        assert_eq!(launchers[0].path, dir.join("cursor").join("github.sh"));
+        assert!(launchers[0].content.contains("exec 'github-mcp' '--stdio'"));
+
+        let ServerConfig::Stdio(github) = &servers["github"] else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(github.command, "/state/launchers/cursor/github.sh");
+        assert!(github.args.is_empty());
+
+        let ServerConfig::Stdio(files) = &servers["local files"] else {
+            panic!("expected stdio server");
+        };
+        assert_eq!(files.command, "files-mcp");
+        assert_eq!(
+            launcher_path(dir, ToolName::Cursor, "local files"),
+            dir.join("cursor").join("local_files.sh")
+        );
+    }
+}
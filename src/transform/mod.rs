@@ -5,14 +5,480 @@
 
 pub mod claude_code;
 pub mod codex;
+pub mod container;
 pub mod cursor;
+pub mod cwd;
+pub mod extra;
 pub mod filter;
+pub mod headers;
+pub mod launcher;
 pub mod opencode;
+pub mod runtime;
 
-pub use claude_code::transform_for_claude_code;
+pub use claude_code::{
+    PermissionRules, claude_code_permissions, merge_permissions, transform_for_claude_code,
+};
 pub use codex::transform_for_codex;
 pub use cursor::transform_for_cursor;
 pub use filter::filter_servers_for_tool;
+pub use launcher::{Launcher, apply_launchers};
 pub use opencode::{
     OpencodeLocalServer, OpencodeRemoteServer, OpencodeServer, transform_for_opencode,
 };
+pub use runtime::render_runtime_refs;
+
+use crate::{
+    config::types::{CwdFallback, RuntimeFallback, ServerConfig, Settings, ToolName},
+    file_ops::default_launcher_dir,
+};
+use std::{collections::HashMap, path::PathBuf};
+
+/// Options shared by every tool transformation
+#[derive(Debug, Clone, Default)]
+pub struct TransformOptions {
+    /// Default targets from settings (empty if none specified)
+    pub default_targets: Vec<String>,
+
+    /// Behaviour for runtime references a tool cannot express
+    pub runtime_fallback: RuntimeFallback,
+
+    /// Behaviour for `cwd` on tools without a working directory field
+    pub cwd_fallback: CwdFallback,
+
+    /// Start STDIO servers through launchers unless they set `launcher`
+    pub launcher: bool,
+
+    /// Directory launchers are written under; `None` uses the default
+    /// under the state directory
+    pub launcher_dir: Option<PathBuf>,
+
+    /// `PATH` exported by launchers for servers whose `env` has none
+    pub search_path: Option<String>,
+}
+
+impl TransformOptions {
+    /// Build options from the optional settings section
+    #[must_use]
+    pub fn from_settings(settings: Option<&Settings>) -> Self {
+        settings.map_or_else(Self::default, |s| Self {
+            default_targets: s.default_targets.clone(),
+            runtime_fallback: s.runtime_fallback,
+            cwd_fallback: s.cwd_fallback,
+            launcher: s.launcher,
+            launcher_dir: None,
+            search_path: s.search_path.clone(),
+        })
+    }
+}
+
+/// Result of transforming servers for one tool
+#[derive(Debug, Clone)]
+pub struct ToolOutput {
+    /// Generated configuration file content
+    pub content: String,
+
+    /// Non-fatal issues encountered during transformation
+    pub warnings: Vec<String>,
+
+    /// Launcher scripts the content refers to, to be written alongside it
+    pub launchers: Vec<Launcher>,
+
+    /// Permission rules for Claude Code's settings file; empty for other
+    /// tools
+    pub permissions: PermissionRules,
+}
+
+/// Transform servers into a tool's configuration format
+///
+/// Applies the tool's overrides, turns container servers into their `run`
+/// command, selects the servers targeting the tool over
+/// a transport it supports, moves servers that use a launcher into their
+/// scripts, applies the working directory fallback, renders runtime variable
+/// references, then runs the tool's transformer.
+///
+/// # Arguments
+///
+/// * `tool` - Tool to generate configuration for
+/// * `servers` - All servers from configuration (expanded)
+/// * `options` - Transformation options
+///
+/// # Returns
+///
+/// * `Ok(ToolOutput)` - Generated content and warnings
+/// * `Err(String)` - Transformation error
+///
+/// # Errors
+///
+/// Returns error if a runtime reference cannot be rendered, serialization
+/// fails, or `tool` is `all`
+#[allow(clippy::implicit_hasher)]
+pub fn transform_for_tool(
+    tool: ToolName,
+    servers: &HashMap<String, ServerConfig>,
+    options: &TransformOptions,
+) -> Result<ToolOutput, String> {
+    let default_targets = &options.default_targets;
+
+    // Apply the tool's overrides first, they can enable or disable a server
+    let servers: HashMap<String, ServerConfig> = servers
+        .iter()
+        .map(|(name, server)| {
+            let mut server = server.for_tool(tool);
+            container::apply_container(&mut server);
+            (name.clone(), server)
+        })
+        .collect();
+    let mut selected = filter_servers_for_tool(&servers, tool, default_targets);
+
+    // Drop servers the tool cannot connect to; only an explicit transport
+    // is worth a warning, inferred HTTP servers were always skipped
+    let mut skipped = Vec::new();
+    selected.retain(|name, server| {
+        let supported = server.transport().supported_by(tool);
+        if !supported && server.explicit_transport().is_some() {
+            skipped.push(format!(
+                "Server '{name}': {tool} does not support the {} transport, skipping",
+                server.transport()
+            ));
+        }
+        supported
+    });
+    skipped.sort();
+
+    let launcher_dir = options
+        .launcher_dir
+        .clone()
+        .unwrap_or_else(default_launcher_dir);
+    let (launchers, mut launcher_warnings) = apply_launchers(
+        &mut selected,
+        tool,
+        options.launcher,
+        &launcher_dir,
+        options.search_path.as_deref(),
+    );
+    skipped.append(&mut launcher_warnings);
+    for server in selected.values_mut() {
+        cwd::apply_cwd(server, tool, options.cwd_fallback);
+    }
+
+    let (servers, mut warnings) = render_runtime_refs(&selected, tool, options.runtime_fallback)?;
+    skipped.append(&mut warnings);
+    let warnings = skipped;
+
+    // Emit servers under their alias for this tool, if any
+    let servers: HashMap<String, ServerConfig> = servers
+        .into_iter()
+        .map(|(name, server)| (server.name_for(&name, tool).to_string(), server))
+        .collect();
+
+    let permissions = if tool == ToolName::ClaudeCode {
+        claude_code_permissions(&servers, default_targets)
+    } else {
+        PermissionRules::default()
+    };
+
+    let content = match tool {
+        ToolName::Cursor => transform_for_cursor(&servers, default_targets)?,
+        ToolName::Opencode => transform_for_opencode(&servers, default_targets)?,
+        ToolName::Codex => transform_for_codex(&servers, default_targets)?,
+        ToolName::ClaudeCode => transform_for_claude_code(&servers, default_targets)?,
+        ToolName::All => return Err("Cannot transform for 'all' tool".to_string()),
+    };
+
+    Ok(ToolOutput {
+        content,
+        warnings,
+        launchers,
+        permissions,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::{
+        ContainerConfig, HttpServerConfig, ServerOverride, StdioServerConfig, ToolPolicy, Transport,
+    };
+
+    fn http_server(transport: Option<Transport>) -> ServerConfig {
+        ServerConfig::Http(HttpServerConfig {
+            url: "https://example.com/mcp".to_string(),
+            transport,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn test_transform_for_tool_applies_overrides() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "github".to_string(),
+            ServerConfig::Stdio(StdioServerConfig {
+                command: "github-mcp".to_string(),
+                overrides: HashMap::from([
+                    (
+                        "codex".to_string(),
+                        ServerOverride {
+                            args: Some(vec!["--read-only".to_string()]),
+                            ..Default::default()
+                        },
+                    ),
+                    (
+                        "cursor".to_string(),
+                        ServerOverride {
+                            enabled: Some(false),
+                            ..Default::default()
+                        },
+                    ),
+                ]),
+                ..Default::default()
+            }),
+        );
+
+        let options = TransformOptions::default();
+        let codex = transform_for_tool(ToolName::Codex, &servers, &options).unwrap();
+        assert!(codex.content.contains("--read-only"));
+        let opencode = transform_for_tool(ToolName::Opencode, &servers, &options).unwrap();
+        assert!(opencode.content.contains("github-mcp"));
+        assert!(!opencode.content.contains("--read-only"));
+        let cursor = transform_for_tool(ToolName::Cursor, &servers, &options).unwrap();
+        assert!(!cursor.content.contains("github-mcp"));
+    }
+
+    #[test]
+    fn test_transform_for_tool_emits_extra() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "github".to_string(),
+            ServerConfig::Stdio(StdioServerConfig {
+                command: "github-mcp".to_string(),
+                extra: HashMap::from([
+                    (
+                        "codex".to_string(),
+                        toml::from_str("enabled_tools = [\"search\"]").unwrap(),
+                    ),
+                    (
+                        "opencode".to_string(),
+                        toml::from_str("timeout = 5000").unwrap(),
+                    ),
+                ]),
+                ..Default::default()
+            }),
+        );
+
+        let options = TransformOptions::default();
+        let codex = transform_for_tool(ToolName::Codex, &servers, &options).unwrap();
+        let parsed: toml::Table = toml::from_str(&codex.content).unwrap();
+        assert_eq!(
+            parsed["mcp_servers"]["github"]["enabled_tools"],
+            toml::Value::Array(vec!["search".into()])
+        );
+        assert!(!codex.content.contains("timeout"));
+
+        let opencode = transform_for_tool(ToolName::Opencode, &servers, &options).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&opencode.content).unwrap();
+        assert_eq!(parsed["mcp"]["github"]["timeout"], 5000);
+        assert_eq!(parsed["mcp"]["github"]["type"], "local");
+    }
+
+    #[test]
+    fn test_transform_for_tool_uses_launchers() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "github".to_string(),
+            ServerConfig::Stdio(StdioServerConfig {
+                command: "github-mcp".to_string(),
+                cwd: Some("/srv/github".to_string()),
+                env: Some(HashMap::from([(
+                    "TOKEN".to_string(),
+                    "${GITHUB_TOKEN}".to_string(),
+                )])),
+                launcher: Some(true),
+                ..Default::default()
+            }),
+        );
+
+        let options = TransformOptions {
+            cwd_fallback: CwdFallback::Wrap,
+            launcher_dir: Some(PathBuf::from("/state/launchers")),
+            ..Default::default()
+        };
+        let cursor = transform_for_tool(ToolName::Cursor, &servers, &options).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&cursor.content).unwrap();
+        let entry = &parsed["mcpServers"]["github"];
+        assert_eq!(entry["command"], "/state/launchers/cursor/github.sh");
+        assert!(entry.get("env").is_none());
+        assert_eq!(entry["args"], serde_json::json!([]));
+
+        // The script keeps the reference for the shell and changes directory
+        assert_eq!(cursor.launchers.len(), 1);
+        let script = &cursor.launchers[0].content;
+        assert!(script.contains("export TOKEN=\"${GITHUB_TOKEN}\""));
+        assert!(script.contains("cd '/srv/github' || exit 1"));
+    }
+
+    #[test]
+    fn test_transform_for_tool_maps_timeouts() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "remote".to_string(),
+            ServerConfig::Http(HttpServerConfig {
+                url: "https://example.com/mcp".to_string(),
+                startup_timeout_sec: Some(20),
+                tool_timeout_sec: Some(120),
+                ..Default::default()
+            }),
+        );
+
+        let options = TransformOptions::default();
+        let codex = transform_for_tool(ToolName::Codex, &servers, &options).unwrap();
+        let parsed: toml::Table = toml::from_str(&codex.content).unwrap();
+        assert_eq!(
+            parsed["mcp_servers"]["remote"]["startup_timeout_sec"].as_integer(),
+            Some(20)
+        );
+        assert_eq!(
+            parsed["mcp_servers"]["remote"]["tool_timeout_sec"].as_integer(),
+            Some(120)
+        );
+
+        // opencode.ai takes milliseconds and has no tool call timeout
+        let opencode = transform_for_tool(ToolName::Opencode, &servers, &options).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&opencode.content).unwrap();
+        assert_eq!(parsed["mcp"]["remote"]["timeout"], 20_000);
+
+        let claude = transform_for_tool(ToolName::ClaudeCode, &servers, &options).unwrap();
+        assert!(!claude.content.contains("timeout"));
+    }
+
+    #[test]
+    fn test_transform_for_tool_translates_tool_policy() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "github".to_string(),
+            ServerConfig::Stdio(StdioServerConfig {
+                command: "github-mcp".to_string(),
+                auto_approve: Some(vec!["get_issue".to_string()]),
+                tools: ToolPolicy {
+                    allow: vec!["search_code".to_string(), "get_issue".to_string()],
+                    deny: vec!["delete_repo".to_string()],
+                    auto_approve: vec!["search_code".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        );
+
+        let options = TransformOptions::default();
+        let codex = transform_for_tool(ToolName::Codex, &servers, &options).unwrap();
+        let parsed: toml::Table = toml::from_str(&codex.content).unwrap();
+        let entry = &parsed["mcp_servers"]["github"];
+        assert_eq!(
+            entry["enabled_tools"],
+            toml::Value::Array(vec!["search_code".into(), "get_issue".into()])
+        );
+        assert_eq!(
+            entry["disabled_tools"],
+            toml::Value::Array(vec!["delete_repo".into()])
+        );
+
+        let cursor = transform_for_tool(ToolName::Cursor, &servers, &options).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&cursor.content).unwrap();
+        assert_eq!(
+            parsed["mcpServers"]["github"]["autoApprove"],
+            serde_json::json!(["get_issue", "search_code"])
+        );
+
+        let opencode = transform_for_tool(ToolName::Opencode, &servers, &options).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&opencode.content).unwrap();
+        assert_eq!(
+            parsed["tools"],
+            serde_json::json!({
+                "github_*": false,
+                "github_search_code": true,
+                "github_get_issue": true,
+                "github_delete_repo": false,
+            })
+        );
+
+        let claude = transform_for_tool(ToolName::ClaudeCode, &servers, &options).unwrap();
+        assert_eq!(claude.permissions.deny, ["mcp__github__delete_repo"]);
+        assert!(codex.permissions.is_empty());
+    }
+
+    #[test]
+    fn test_transform_for_tool_runs_containers() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "github".to_string(),
+            ServerConfig::Stdio(StdioServerConfig {
+                args: vec!["stdio".to_string()],
+                container: Some(ContainerConfig {
+                    image: "ghcr.io/github/github-mcp-server".to_string(),
+                    env: HashMap::from([("GITHUB_TOKEN".to_string(), "ghp_x".to_string())]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+        );
+
+        let options = TransformOptions::default();
+        let codex = transform_for_tool(ToolName::Codex, &servers, &options).unwrap();
+        let parsed: toml::Table = toml::from_str(&codex.content).unwrap();
+        let entry = &parsed["mcp_servers"]["github"];
+        assert_eq!(entry["command"].as_str(), Some("docker"));
+        assert_eq!(
+            entry["args"],
+            toml::Value::Array(vec![
+                "run".into(),
+                "-i".into(),
+                "--rm".into(),
+                "-e".into(),
+                "GITHUB_TOKEN".into(),
+                "ghcr.io/github/github-mcp-server".into(),
+                "stdio".into(),
+            ])
+        );
+        assert_eq!(entry["env"]["GITHUB_TOKEN"].as_str(), Some("ghp_x"));
+    }
+
+    #[test]
+    fn test_transform_for_tool_uses_aliases() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "github.com".to_string(),
+            ServerConfig::Http(HttpServerConfig {
+                url: "https://example.com/mcp".to_string(),
+                aliases: HashMap::from([("codex".to_string(), "gh".to_string())]),
+                ..Default::default()
+            }),
+        );
+
+        let options = TransformOptions::default();
+        let codex = transform_for_tool(ToolName::Codex, &servers, &options).unwrap();
+        assert!(codex.content.contains("[mcp_servers.gh]"));
+        let claude = transform_for_tool(ToolName::ClaudeCode, &servers, &options).unwrap();
+        assert!(claude.content.contains("\"github.com\""));
+    }
+
+    #[test]
+    fn test_transform_for_tool_skips_unsupported_transport() {
+        let mut servers = HashMap::new();
+        servers.insert("events".to_string(), http_server(Some(Transport::Sse)));
+        servers.insert("stream".to_string(), http_server(None));
+
+        let options = TransformOptions::default();
+        let codex = transform_for_tool(ToolName::Codex, &servers, &options).unwrap();
+        assert!(!codex.content.contains("events"));
+        assert!(codex.content.contains("stream"));
+        assert_eq!(
+            codex.warnings,
+            vec!["Server 'events': codex does not support the sse transport, skipping"]
+        );
+
+        // Inferred HTTP servers are skipped for Cursor without a warning
+        let cursor = transform_for_tool(ToolName::Cursor, &servers, &options).unwrap();
+        assert_eq!(cursor.warnings.len(), 1);
+        assert!(cursor.warnings[0].starts_with("Server 'events'"));
+    }
+}
@@ -2,16 +2,20 @@
 
 use crate::{
     config::types::{HttpServerConfig, ServerConfig, StdioServerConfig, ToolName},
-    transform::filter::filter_servers_for_tool,
+    transform::{extra::json_entry, filter::filter_servers_for_tool, headers::all_headers},
 };
 use serde::Serialize;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 /// opencode.ai configuration output structure
 #[derive(Debug, Serialize)]
 struct OpencodeConfig {
     /// MCP servers for opencode.ai
-    mcp: HashMap<String, OpencodeServer>,
+    mcp: HashMap<String, serde_json::Value>,
+
+    /// Tools switched on or off, as `<server>_<tool>` or `<server>_*`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    tools: BTreeMap<String, bool>,
 }
 
 /// opencode.ai server configuration (STDIO or HTTP)
@@ -27,9 +31,9 @@ pub enum OpencodeServer {
 /// opencode.ai local STDIO server
 #[derive(Debug, Serialize)]
 pub struct OpencodeLocalServer {
-    /// Server type (always "local")
+    /// Server type ("local"; Claude Code uses "stdio")
     #[serde(rename = "type")]
-    server_type: String,
+    pub(crate) server_type: String,
 
     /// Command as array: [executable, ...args]
     command: Vec<String>,
@@ -40,24 +44,37 @@ pub struct OpencodeLocalServer {
 
     /// Whether server is enabled
     enabled: bool,
+
+    /// Milliseconds to wait for the server's tools (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout: Option<u64>,
 }
 
 /// opencode.ai remote HTTP server
 #[derive(Debug, Serialize)]
 pub struct OpencodeRemoteServer {
-    /// Server type (always "remote")
+    /// Server type ("remote"; Claude Code uses "sse" or "http")
     #[serde(rename = "type")]
-    server_type: String,
+    pub(crate) server_type: String,
 
     /// Server URL
     url: String,
 
-    /// Headers (optional, for bearer token)
+    /// Headers (optional, including authentication)
     #[serde(skip_serializing_if = "Option::is_none")]
-    headers: Option<HashMap<String, String>>,
+    headers: Option<BTreeMap<String, String>>,
 
     /// Whether server is enabled
     enabled: bool,
+
+    /// Milliseconds to wait for the server's tools (optional)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) timeout: Option<u64>,
+}
+
+/// Convert a timeout in seconds to opencode.ai's milliseconds
+fn timeout_ms(seconds: u32) -> u64 {
+    u64::from(seconds) * 1000
 }
 
 /// Transform servers to opencode.ai JSON format
@@ -86,17 +103,32 @@ pub fn transform_for_opencode(
     let filtered = filter_servers_for_tool(servers, ToolName::Opencode, default_targets);
 
     let mut opencode_servers = HashMap::new();
+    let mut tools = BTreeMap::new();
 
     for (name, server) in filtered {
-        let opencode_server = match server {
-            ServerConfig::Stdio(stdio) => OpencodeServer::Local(transform_stdio_server(&stdio)),
-            ServerConfig::Http(http) => OpencodeServer::Remote(transform_http_server(&http)),
+        // opencode.ai names MCP tools `<server>_<tool>`; an allow list
+        // switches every tool of the server off and the allowed ones back on
+        let policy = server.tools();
+        if !policy.allow.is_empty() {
+            tools.insert(format!("{name}_*"), false);
+            for tool in &policy.allow {
+                tools.insert(format!("{name}_{tool}"), true);
+            }
+        }
+        for tool in &policy.deny {
+            tools.insert(format!("{name}_{tool}"), false);
+        }
+        let opencode_server = match &server {
+            ServerConfig::Stdio(stdio) => OpencodeServer::Local(transform_stdio_server(stdio)),
+            ServerConfig::Http(http) => OpencodeServer::Remote(transform_http_server(http)),
         };
-        opencode_servers.insert(name, opencode_server);
+        let entry = json_entry(&opencode_server, server.extra_for(ToolName::Opencode))?;
+        opencode_servers.insert(name, entry);
     }
 
     let opencode_config = OpencodeConfig {
         mcp: opencode_servers,
+        tools,
     };
 
     // Serialize to JSON with 2-space indentation
@@ -116,30 +148,29 @@ pub fn transform_stdio_server(stdio: &StdioServerConfig) -> OpencodeLocalServer
         command,
         env: stdio.env.clone(),
         enabled: stdio.enabled,
+        timeout: stdio.startup_timeout_sec.map(timeout_ms),
     }
 }
 
 /// Transform an HTTP server to opencode.ai remote format
 #[must_use]
 pub fn transform_http_server(http: &HttpServerConfig) -> OpencodeRemoteServer {
-    // Convert bearer_token to Authorization header if present
-    let headers = http.bearer_token.as_ref().map(|token| {
-        let mut headers = HashMap::new();
-        headers.insert("Authorization".to_string(), format!("Bearer {token}"));
-        headers
-    });
+    // Custom headers and authentication all become headers
+    let headers = Some(all_headers(http)).filter(|headers| !headers.is_empty());
 
     OpencodeRemoteServer {
         server_type: "remote".to_string(),
         url: http.url.clone(),
         headers,
         enabled: http.enabled,
+        timeout: http.startup_timeout_sec.map(timeout_ms),
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::types::HttpAuth;
 
     fn create_stdio_server(command: &str, args: Vec<String>, targets: Vec<String>) -> ServerConfig {
         ServerConfig::Stdio(StdioServerConfig {
@@ -152,6 +183,7 @@ mod tests {
             auto_approve: None,
             startup_timeout_sec: None,
             tool_timeout_sec: None,
+            ..Default::default()
         })
     }
 
@@ -165,6 +197,7 @@ mod tests {
             bearer_token,
             enabled: true,
             targets,
+            ..Default::default()
         })
     }
 
@@ -233,6 +266,7 @@ mod tests {
                 auto_approve: None,
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
+                ..Default::default()
             }),
         );
 
@@ -307,4 +341,31 @@ mod tests {
         let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
         assert!(parsed["mcp"].as_object().unwrap().is_empty());
     }
+
+    #[test]
+    fn test_transform_opencode_headers_and_basic_auth() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "remote".to_string(),
+            ServerConfig::Http(HttpServerConfig {
+                url: "https://example.com/mcp".to_string(),
+                headers: Some(HashMap::from([(
+                    "X-Team".to_string(),
+                    "platform".to_string(),
+                )])),
+                auth: Some(HttpAuth::Basic {
+                    username: "user".to_string(),
+                    password: "pass".to_string(),
+                }),
+                ..Default::default()
+            }),
+        );
+
+        let json = transform_for_opencode(&servers, &[]).unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        let headers = &parsed["mcp"]["remote"]["headers"];
+        assert_eq!(headers["X-Team"], "platform");
+        assert_eq!(headers["Authorization"], "Basic dXNlcjpwYXNz");
+    }
 }
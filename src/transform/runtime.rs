@@ -0,0 +1,355 @@
+//! Rendering of deferred runtime variable references
+//!
+//! Servers expanded with `expand = "runtime"` keep their shell references as
+//! `${VAR}`. Before a tool transformer runs, each reference is rewritten into
+//! that tool's own interpolation syntax, or handled according to
+//! `settings.runtime_fallback` where the tool has none.
+
+use crate::config::types::{HttpAuth, RuntimeFallback, ServerConfig, ToolName};
+use regex::Regex;
+use std::{collections::HashMap, sync::LazyLock};
+
+/// Pattern matching a deferred shell reference
+pub(crate) static RUNTIME_REF: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\$\{([^}]+)\}").unwrap());
+
+/// Server field a value came from, used for tool support checks and messages
+#[derive(Debug, Clone, Copy)]
+enum Field<'a> {
+    /// STDIO command
+    Command,
+    /// STDIO argument
+    Arg,
+    /// STDIO environment variable with the given key
+    Env(&'a str),
+    /// HTTP URL
+    Url,
+    /// HTTP bearer token
+    BearerToken,
+    /// HTTP header with the given name
+    Header(&'a str),
+    /// Value of a header-based `auth`
+    AuthHeaderValue,
+    /// Basic `auth` user name or password
+    BasicAuth(&'static str),
+}
+
+impl std::fmt::Display for Field<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Command => write!(f, "command"),
+            Self::Arg => write!(f, "args"),
+            Self::Env(key) => write!(f, "env.{key}"),
+            Self::Url => write!(f, "url"),
+            Self::BearerToken => write!(f, "bearer_token"),
+            Self::Header(name) => write!(f, "headers.{name}"),
+            Self::AuthHeaderValue => write!(f, "auth.value"),
+            Self::BasicAuth(part) => write!(f, "auth.{part}"),
+        }
+    }
+}
+
+/// Return the variable name if `value` is exactly one runtime reference
+#[must_use]
+pub fn exact_runtime_ref(value: &str) -> Option<&str> {
+    value
+        .strip_prefix("${")
+        .and_then(|rest| rest.strip_suffix('}'))
+        .filter(|name| !name.is_empty() && !name.contains(['{', '}']))
+}
+
+/// Render runtime references for a tool
+///
+/// Cursor, opencode.ai and Claude Code can interpolate environment variables
+/// in any field. Codex can only forward an environment variable under its
+/// own name (`env_vars`) and read a bearer token or header from one
+/// (`bearer_token_env_var`, `env_http_headers`); those references are left
+/// as `${VAR}` for the Codex transformer. Basic auth credentials are
+/// base64-encoded at compile time, so no tool can defer them. Everything
+/// else goes through `fallback`.
+///
+/// # Arguments
+///
+/// * `servers` - Expanded servers
+/// * `tool` - Tool being rendered for
+/// * `fallback` - Behaviour for references the tool cannot express
+///
+/// # Returns
+///
+/// * `Ok((servers, warnings))` - Rendered servers and fallback warnings
+/// * `Err(String)` - A reference cannot be expressed and fallback is `error`
+///
+/// # Errors
+///
+/// Returns error if the tool cannot express a reference and `fallback` is
+/// [`RuntimeFallback::Error`]
+#[allow(clippy::implicit_hasher)]
+pub fn render_runtime_refs(
+    servers: &HashMap<String, ServerConfig>,
+    tool: ToolName,
+    fallback: RuntimeFallback,
+) -> Result<(HashMap<String, ServerConfig>, Vec<String>), String> {
+    let mut rendered = HashMap::new();
+    let mut warnings = Vec::new();
+
+    for (name, server) in servers {
+        let mut server = server.clone();
+        let mut render = |field: Field<'_>, value: &mut String| -> Result<(), String> {
+            *value = render_value(name, field, value, tool, fallback, &mut warnings)?;
+            Ok(())
+        };
+
+        match &mut server {
+            ServerConfig::Stdio(stdio) => {
+                render(Field::Command, &mut stdio.command)?;
+                for arg in &mut stdio.args {
+                    render(Field::Arg, arg)?;
+                }
+                if let Some(env) = &mut stdio.env {
+                    for (key, value) in env.iter_mut() {
+                        render(Field::Env(key), value)?;
+                    }
+                }
+            }
+            ServerConfig::Http(http) => {
+                render(Field::Url, &mut http.url)?;
+                if let Some(token) = &mut http.bearer_token {
+                    render(Field::BearerToken, token)?;
+                }
+                if let Some(headers) = &mut http.headers {
+                    for (name, value) in headers.iter_mut() {
+                        render(Field::Header(name), value)?;
+                    }
+                }
+                match &mut http.auth {
+                    Some(HttpAuth::Bearer { token }) => render(Field::BearerToken, token)?,
+                    Some(HttpAuth::Basic { username, password }) => {
+                        render(Field::BasicAuth("username"), username)?;
+                        render(Field::BasicAuth("password"), password)?;
+                    }
+                    Some(HttpAuth::Header { value, .. }) => {
+                        render(Field::AuthHeaderValue, value)?;
+                    }
+                    None => {}
+                }
+            }
+        }
+
+        rendered.insert(name.clone(), server);
+    }
+
+    Ok((rendered, warnings))
+}
+
+/// Render the runtime references in a single value
+fn render_value(
+    server_name: &str,
+    field: Field<'_>,
+    value: &str,
+    tool: ToolName,
+    fallback: RuntimeFallback,
+    warnings: &mut Vec<String>,
+) -> Result<String, String> {
+    if !RUNTIME_REF.is_match(value) {
+        return Ok(value.to_string());
+    }
+
+    if tool_supports(tool, field, value) {
+        return Ok(match tool {
+            ToolName::Cursor => RUNTIME_REF.replace_all(value, "$${env:$1}").into_owned(),
+            ToolName::Opencode => RUNTIME_REF.replace_all(value, "{env:$1}").into_owned(),
+            ToolName::Codex | ToolName::ClaudeCode | ToolName::All => value.to_string(),
+        });
+    }
+
+    match fallback {
+        RuntimeFallback::Inline => {
+            warnings.push(format!(
+                "Server '{server_name}': {tool} cannot reference environment variables at runtime \
+                 in '{field}', inlining current value"
+            ));
+            Ok(RUNTIME_REF
+                .replace_all(value, |caps: &regex::Captures<'_>| {
+                    std::env::var(&caps[1]).unwrap_or_default()
+                })
+                .into_owned())
+        }
+        RuntimeFallback::Error => Err(format!(
+            "Server '{server_name}': {tool} cannot reference environment variables at runtime in \
+             '{field}' (set runtime_fallback = \"inline\" to substitute the value)"
+        )),
+    }
+}
+
+/// Whether a tool can express a runtime reference in this field
+fn tool_supports(tool: ToolName, field: Field<'_>, value: &str) -> bool {
+    match field {
+        // Encoded into the header at compile time
+        Field::BasicAuth(_) => false,
+        _ if tool != ToolName::Codex => true,
+        // env_vars forwards a variable under its own name only
+        Field::Env(key) => exact_runtime_ref(value) == Some(key),
+        Field::BearerToken | Field::Header(_) | Field::AuthHeaderValue => {
+            exact_runtime_ref(value).is_some()
+        }
+        Field::Command | Field::Arg | Field::Url => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::types::{HttpServerConfig, StdioServerConfig};
+
+    fn servers_with_env(key: &str, value: &str) -> HashMap<String, ServerConfig> {
+        let mut env = HashMap::new();
+        env.insert(key.to_string(), value.to_string());
+
+        let mut servers = HashMap::new();
+        servers.insert(
+            "test".to_string(),
+            ServerConfig::Stdio(StdioServerConfig {
+                command: "npx".to_string(),
+                args: vec!["--token=${GH_TOKEN}".to_string()],
+                env: Some(env),
+                ..Default::default()
+            }),
+        );
+        servers
+    }
+
+    fn stdio(servers: &HashMap<String, ServerConfig>) -> &StdioServerConfig {
+        match servers.get("test") {
+            Some(ServerConfig::Stdio(stdio)) => stdio,
+            _ => panic!("Expected STDIO server"),
+        }
+    }
+
+    #[test]
+    fn test_exact_runtime_ref() {
+        assert_eq!(exact_runtime_ref("${TOKEN}"), Some("TOKEN"));
+        assert_eq!(exact_runtime_ref("Bearer ${TOKEN}"), None);
+        assert_eq!(exact_runtime_ref("${}"), None);
+        assert_eq!(exact_runtime_ref("plain"), None);
+    }
+
+    #[test]
+    #[allow(clippy::literal_string_with_formatting_args)]
+    fn test_render_cursor_syntax() {
+        let servers = servers_with_env("GH_TOKEN", "${GH_TOKEN}");
+        let (rendered, warnings) =
+            render_runtime_refs(&servers, ToolName::Cursor, RuntimeFallback::Error).unwrap();
+
+        let server = stdio(&rendered);
+        assert_eq!(server.args[0], "--token=${env:GH_TOKEN}");
+        assert_eq!(server.env.as_ref().unwrap()["GH_TOKEN"], "${env:GH_TOKEN}");
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_render_opencode_syntax() {
+        let servers = servers_with_env("GH_TOKEN", "${GH_TOKEN}");
+        let (rendered, _) =
+            render_runtime_refs(&servers, ToolName::Opencode, RuntimeFallback::Error).unwrap();
+
+        assert_eq!(
+            stdio(&rendered).env.as_ref().unwrap()["GH_TOKEN"],
+            "{env:GH_TOKEN}"
+        );
+    }
+
+    #[test]
+    fn test_render_claude_code_keeps_syntax() {
+        let servers = servers_with_env("GH_TOKEN", "${GH_TOKEN}");
+        let (rendered, _) =
+            render_runtime_refs(&servers, ToolName::ClaudeCode, RuntimeFallback::Error).unwrap();
+
+        assert_eq!(stdio(&rendered).args[0], "--token=${GH_TOKEN}");
+    }
+
+    #[test]
+    fn test_render_codex_error_fallback() {
+        let servers = servers_with_env("GH_TOKEN", "${GH_TOKEN}");
+        let result = render_runtime_refs(&servers, ToolName::Codex, RuntimeFallback::Error);
+
+        // The env entry is expressible, the argument is not
+        let err = result.unwrap_err();
+        assert!(err.contains("'args'"));
+    }
+
+    #[test]
+    fn test_render_codex_inline_fallback() {
+        std::env::set_var("TEST_RENDER_CODEX_TOKEN", "inlined_value");
+
+        let mut servers = servers_with_env("API_KEY", "${TEST_RENDER_CODEX_TOKEN}");
+        if let Some(ServerConfig::Stdio(stdio)) = servers.get_mut("test") {
+            stdio.args.clear();
+        }
+
+        let (rendered, warnings) =
+            render_runtime_refs(&servers, ToolName::Codex, RuntimeFallback::Inline).unwrap();
+
+        // Key differs from the variable name, so Codex can't forward it
+        assert_eq!(
+            stdio(&rendered).env.as_ref().unwrap()["API_KEY"],
+            "inlined_value"
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("env.API_KEY"));
+
+        std::env::remove_var("TEST_RENDER_CODEX_TOKEN");
+    }
+
+    #[test]
+    fn test_render_codex_bearer_token() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "remote".to_string(),
+            ServerConfig::Http(HttpServerConfig {
+                url: "https://example.com".to_string(),
+                bearer_token: Some("${API_TOKEN}".to_string()),
+                ..Default::default()
+            }),
+        );
+
+        let (rendered, warnings) =
+            render_runtime_refs(&servers, ToolName::Codex, RuntimeFallback::Error).unwrap();
+
+        match rendered.get("remote") {
+            Some(ServerConfig::Http(http)) => {
+                assert_eq!(http.bearer_token.as_deref(), Some("${API_TOKEN}"));
+            }
+            _ => panic!("Expected HTTP server"),
+        }
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_render_codex_headers_and_basic_auth() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "remote".to_string(),
+            ServerConfig::Http(HttpServerConfig {
+                url: "https://example.com".to_string(),
+                headers: Some(HashMap::from([(
+                    "X-API-Key".to_string(),
+                    "${API_KEY}".to_string(),
+                )])),
+                auth: Some(HttpAuth::Basic {
+                    username: "bot".to_string(),
+                    password: "${API_PASSWORD}".to_string(),
+                }),
+                ..Default::default()
+            }),
+        );
+
+        // The header is deferred; basic auth has to be encoded at compile time
+        let err =
+            render_runtime_refs(&servers, ToolName::Codex, RuntimeFallback::Error).unwrap_err();
+        assert!(err.contains("'auth.password'"));
+
+        let err =
+            render_runtime_refs(&servers, ToolName::Cursor, RuntimeFallback::Error).unwrap_err();
+        assert!(err.contains("'auth.password'"));
+    }
+}
@@ -162,6 +162,46 @@ args = ["-y", "package"]
     assert!(stdout.contains("valid"));
 }
 
+/// Test validate command with JSON diagnostics
+#[test]
+fn test_validate_command_json_diagnostics() {
+    use std::fs;
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+
+    let invalid_config = r#"[settings]
+version = "1.0"
+
+[mcp.servers.test]
+command = "npx"
+targets = ["vim"]
+"#;
+    fs::write(&config_path, invalid_config).unwrap();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "--bin",
+            "multi-agent-config",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+            "validate",
+            "--format",
+            "json",
+        ])
+        .output()
+        .expect("Failed to execute binary");
+
+    assert!(!output.status.success());
+    let diagnostics: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(diagnostics[0]["severity"], "error");
+    assert_eq!(diagnostics[0]["labels"][0]["line"], 6);
+    assert_eq!(diagnostics[0]["labels"][0]["column"], 12);
+}
+
 /// Test compile command with dry-run
 #[test]
 fn test_compile_command() {
@@ -200,6 +240,131 @@ targets = ["cursor"]
     assert!(stdout.contains("Would write") || stdout.contains("Dry run"));
 }
 
+/// Test lock command
+#[test]
+fn test_lock_command_pins_packages() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let lock_path = temp_dir.path().join("packages.lock");
+    std::fs::write(
+        &config_path,
+        r#"
+[settings]
+version = "1.0"
+
+[mcp.servers.github]
+package = "npm:@modelcontextprotocol/server-github"
+targets = ["cursor"]
+"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "multi-agent-config",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+            ])
+            .args(args)
+            .output()
+            .expect("Failed to execute binary")
+    };
+
+    // Unpinned and unlocked
+    let output = run(&["validate", "--deny-warnings"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("W011"));
+
+    // The locked version is kept without asking the registry
+    std::fs::write(
+        &lock_path,
+        "[packages]\n\"npm:@modelcontextprotocol/server-github\" = \"1.4.2\"\n\"npm:gone\" = \
+         \"1.0.0\"\n",
+    )
+    .unwrap();
+    assert!(run(&["lock"]).status.success());
+    let lock = std::fs::read_to_string(&lock_path).unwrap();
+    assert!(lock.contains("\"npm:@modelcontextprotocol/server-github\" = \"1.4.2\""));
+    assert!(!lock.contains("npm:gone"));
+
+    let output = run(&["validate"]);
+    assert!(output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("W011"));
+}
+
+/// Test editing servers in place
+#[test]
+fn test_edit_commands() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    std::fs::write(
+        &config_path,
+        "# Team config\n[settings]\nversion = \"1.0\"\n",
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "multi-agent-config",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+            ])
+            .args(args)
+            .output()
+            .expect("Failed to execute binary")
+    };
+
+    let add = [
+        "add",
+        "myserver",
+        "--command",
+        "npx",
+        "--arg",
+        "-y",
+        "--arg",
+        "pkg",
+        "--target",
+        "cursor",
+        "--env",
+        "KEY={TOKEN}",
+    ];
+    assert!(run(&add).status.success());
+    assert!(run(&["disable", "myserver"]).status.success());
+    assert!(
+        run(&["set", "myserver.targets", "cursor,codex"])
+            .status
+            .success()
+    );
+    assert!(!run(&["set", "myserver.targets", "nope"]).status.success());
+    assert!(!run(&["add", "other"]).status.success());
+
+    assert_eq!(
+        std::fs::read_to_string(&config_path).unwrap(),
+        r#"# Team config
+[settings]
+version = "1.0"
+
+[mcp.servers.myserver]
+transport = "stdio"
+command = "npx"
+args = ["-y", "pkg"]
+targets = ["cursor", "codex"]
+env = { KEY = "{TOKEN}" }
+enabled = false
+"#
+    );
+}
+
 /// Test diff command
 #[test]
 fn test_diff_command() {
@@ -237,6 +402,256 @@ targets = ["cursor"]
     assert!(stdout.contains("===") || stdout.contains("---") || stdout.contains("+++"));
 }
 
+/// Test that compile pins resolved commands and status warns when they vanish
+#[test]
+#[cfg(unix)]
+fn test_status_reports_missing_resolved_command() {
+    use std::os::unix::fs::PermissionsExt;
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let bin_dir = temp_dir.path().join("bin");
+    std::fs::create_dir(&bin_dir).unwrap();
+    let server_bin = bin_dir.join("fake-server");
+    std::fs::write(&server_bin, "#!/bin/sh\n").unwrap();
+    std::fs::set_permissions(&server_bin, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let config_path = temp_dir.path().join("config.toml");
+    let config = format!(
+        r#"
+[settings]
+version = "1.0"
+search_path = "{}"
+
+[mcp.servers.test]
+command = "fake-server"
+targets = ["cursor"]
+resolve_command = true
+"#,
+        bin_dir.display()
+    );
+    std::fs::write(&config_path, config).unwrap();
+
+    let run = |command: &str| {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "multi-agent-config",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+                command,
+            ])
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("xdg"))
+            .output()
+            .expect("Failed to execute binary")
+    };
+
+    let output = run("compile");
+    assert!(output.status.success());
+    let cursor_config = std::fs::read_to_string(
+        temp_dir
+            .path()
+            .join("xdg/Cursor/User/globalStorage/saoudrizwan.claude-dev/settings/mcp.json"),
+    )
+    .unwrap();
+    assert!(cursor_config.contains(server_bin.to_str().unwrap()));
+
+    let output = run("status");
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("test: fake-server -> {}", server_bin.display())));
+
+    std::fs::remove_file(&server_bin).unwrap();
+    let output = run("status");
+    assert!(output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Warning: Server 'test': resolved command does not exist"));
+}
+
+/// Test that compile writes launchers and removes them once unused
+#[test]
+#[cfg(unix)]
+fn test_compile_writes_and_removes_launchers() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let write_config = |launcher: bool| {
+        let config = format!(
+            r#"
+[settings]
+version = "1.0"
+
+[mcp.servers.test]
+command = "echo"
+args = ["hello"]
+targets = ["cursor"]
+launcher = {launcher}
+"#
+        );
+        std::fs::write(&config_path, config).unwrap();
+    };
+    let compile = || {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "multi-agent-config",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+                "compile",
+            ])
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("xdg"))
+            .output()
+            .expect("Failed to execute binary")
+    };
+    let launcher = temp_dir
+        .path()
+        .join("xdg/multi-agent-config/state/launchers/cursor/test.sh");
+
+    write_config(true);
+    assert!(compile().status.success());
+    let script = std::fs::read_to_string(&launcher).unwrap();
+    assert!(script.contains("exec 'echo' 'hello' 2>>"));
+    let cursor_config = std::fs::read_to_string(
+        temp_dir
+            .path()
+            .join("xdg/Cursor/User/globalStorage/saoudrizwan.claude-dev/settings/mcp.json"),
+    )
+    .unwrap();
+    assert!(cursor_config.contains(launcher.to_str().unwrap()));
+
+    write_config(false);
+    assert!(compile().status.success());
+    assert!(!launcher.exists());
+}
+
+/// Test that compile keeps Claude Code permission rules in its settings
+#[test]
+fn test_compile_writes_claude_permissions() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+    let settings_path = temp_dir.path().join("home/.claude/settings.json");
+    std::fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+    std::fs::write(
+        &settings_path,
+        r#"{"model": "opus", "permissions": {"allow": ["Bash(ls)"]}}"#,
+    )
+    .unwrap();
+
+    let write_config = |deny: &str| {
+        let config = format!(
+            r#"
+[settings]
+version = "1.0"
+
+[mcp.servers.github]
+url = "https://api.githubcopilot.com/mcp/"
+targets = ["claude-code"]
+tools = {{ deny = [{deny}], auto_approve = ["search_code"] }}
+"#
+        );
+        std::fs::write(&config_path, config).unwrap();
+    };
+    let compile = || {
+        Command::new("cargo")
+            .args([
+                "run",
+                "--bin",
+                "multi-agent-config",
+                "--",
+                "--config",
+                config_path.to_str().unwrap(),
+                "compile",
+            ])
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("xdg"))
+            .env("HOME", temp_dir.path().join("home"))
+            .output()
+            .expect("Failed to execute binary")
+    };
+    let settings = || -> serde_json::Value {
+        serde_json::from_str(&std::fs::read_to_string(&settings_path).unwrap()).unwrap()
+    };
+
+    write_config("\"delete_repo\"");
+    assert!(compile().status.success());
+    let written = settings();
+    assert_eq!(written["model"], "opus");
+    assert_eq!(
+        written["permissions"]["allow"],
+        serde_json::json!(["Bash(ls)", "mcp__github__search_code"])
+    );
+    assert_eq!(
+        written["permissions"]["deny"],
+        serde_json::json!(["mcp__github__delete_repo"])
+    );
+
+    // Rules dropped from the config are removed, others are kept
+    write_config("");
+    assert!(compile().status.success());
+    let written = settings();
+    assert_eq!(written["permissions"]["deny"], serde_json::json!([]));
+    assert_eq!(
+        written["permissions"]["allow"],
+        serde_json::json!(["Bash(ls)", "mcp__github__search_code"])
+    );
+}
+
+/// Test that diff masks secrets unless --show-secrets is given
+#[test]
+fn test_diff_redacts_secrets() {
+    use tempfile::TempDir;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("config.toml");
+
+    let config = r#"
+[settings]
+version = "1.0"
+
+[mcp.servers.test]
+command = "npx"
+targets = ["cursor"]
+
+[mcp.servers.test.env]
+SERVICE_API_KEY = "integration_secret_value"
+"#;
+    std::fs::write(&config_path, config).unwrap();
+
+    let run = |extra: &[&str]| {
+        let mut args = vec![
+            "run",
+            "--bin",
+            "multi-agent-config",
+            "--",
+            "--config",
+            config_path.to_str().unwrap(),
+            "diff",
+        ];
+        args.extend_from_slice(extra);
+        Command::new("cargo")
+            .args(&args)
+            .output()
+            .expect("Failed to execute binary")
+    };
+
+    let output = run(&[]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(!stdout.contains("integration_secret_value"));
+    assert!(stdout.contains("<redacted>"));
+
+    let output = run(&["--show-secrets"]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("integration_secret_value"));
+}
+
 /// Test doctor command
 #[test]
 fn test_doctor_command() {